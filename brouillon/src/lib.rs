@@ -122,11 +122,11 @@ rng_choice!(
 );
 
     fn parse_omega(o_arg: &str, _verbose: bool) -> Vec<String> {
-        o_arg.split(',').map(|s| String::from(s)).collect()
+        o_arg.split(',').map(String::from).collect()
     }
 
     // need omega to set equiprobable law
-    fn parse_law(args: &Cli, omega: &Vec<String>, _verbose: bool) -> Vec<f64> {
+    fn parse_law(args: &Cli, omega: &[String], _verbose: bool) -> Vec<f64> {
         let omega_n = omega.len();
 
         match &args.law {
@@ -158,7 +158,7 @@ rng_choice!(
                     if _verbose {
                         println!("Law sum is {}. Normalizing to 1.0.", law_sum);
                     }
-                    res.iter_mut().for_each(|x| *x = *x / law_sum );
+                    res.iter_mut().for_each(|x| *x /= law_sum );
                 }
     
                 res
@@ -177,6 +177,7 @@ rng_choice!(
         pub verbose: bool
     }
     impl Config {
+        #[allow(clippy::new_without_default)]
         pub fn new() -> Self {
             let cli = Cli::parse();
             if cli.rnglist {
@@ -193,7 +194,7 @@ rng_choice!(
             }
 
             let omega = match &cli.omega {
-                Some(omega) => parse_omega(&omega, verbose),
+                Some(omega) => parse_omega(omega, verbose),
                 None => {
                     println!("--omega <OMEGA> samples space mandatory argument !");
                     process::exit(1);
@@ -206,7 +207,7 @@ rng_choice!(
                 None => rand::random::<u64>()
             };
 
-            let rng_id= String::from(cli.rng);
+            let rng_id= cli.rng;
             let rng = RngChoice::new(&rng_id, rng_seed);
 
             Config { 
@@ -0,0 +1,133 @@
+//! Multi-armed bandit policies, each arm is an experiment giving rewards.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Result of a bandit simulation.
+#[derive(Debug, Clone)]
+pub struct BanditResult {
+    /// Sum of the rewards obtained.
+    pub total_reward: f64,
+    /// `n_rounds * best_mean - sum of the means of the pulled arms`.
+    pub cumulative_regret: f64,
+    /// Number of pulls of each arm.
+    pub pull_counts: Vec<usize>,
+}
+
+/// Running state shared by the policies.
+struct BanditState<'a> {
+    arms: &'a [DiscreteFiniteRandomExperiment<f64>],
+    means: Vec<f64>,
+    estimates: Vec<f64>,
+    pull_counts: Vec<usize>,
+    total_reward: f64,
+    regret: f64,
+    best_mean: f64,
+}
+
+impl<'a> BanditState<'a> {
+    fn new(arms: &'a [DiscreteFiniteRandomExperiment<f64>]) -> Self {
+        assert!(!arms.is_empty(), "at least one arm is required");
+        let means: Vec<f64> = arms.iter().map(|a| a.expected_value()).collect();
+        let best_mean = means.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        BanditState {
+            arms,
+            means,
+            estimates: vec![0.0; arms.len()],
+            pull_counts: vec![0; arms.len()],
+            total_reward: 0.0,
+            regret: 0.0,
+            best_mean,
+        }
+    }
+
+    fn pull<R: Rng + ?Sized>(&mut self, rng: &mut R, arm: usize) {
+        let reward = self.arms[arm].sample(rng);
+        self.pull_counts[arm] += 1;
+        self.estimates[arm] += (reward - self.estimates[arm]) / self.pull_counts[arm] as f64;
+        self.total_reward += reward;
+        self.regret += self.best_mean - self.means[arm];
+    }
+
+    fn untried(&self) -> Option<usize> {
+        self.pull_counts.iter().position(|c| *c == 0)
+    }
+
+    fn result(self) -> BanditResult {
+        BanditResult {
+            total_reward: self.total_reward,
+            cumulative_regret: self.regret,
+            pull_counts: self.pull_counts,
+        }
+    }
+}
+
+fn argmax(values: impl Iterator<Item = f64>) -> usize {
+    values.enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (i, v)| if v > best.1 { (i, v) } else { best })
+        .0
+}
+
+/// Bandit policies. `self` is not used, the `arms` are the bandits.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Epsilon-greedy: explore a random arm with probability `epsilon`,
+    /// otherwise pull the arm with the best estimated mean. Each arm is tried once first.
+    pub fn simulate_epsilon_greedy<R: Rng>(&self, arms: Vec<DiscreteFiniteRandomExperiment<f64>>, rng: &mut R, n_rounds: usize, epsilon: f64) -> BanditResult {
+        let mut state = BanditState::new(&arms);
+        for _ in 0..n_rounds {
+            let arm = match state.untried() {
+                Some(arm) => arm,
+                None if rng.random::<f64>() < epsilon => rng.random_range(0..arms.len()),
+                None => argmax(state.estimates.iter().cloned()),
+            };
+            state.pull(rng, arm);
+        }
+        state.result()
+    }
+
+    /// UCB1: pull the arm maximizing `estimate + sqrt(2 ln t / n_i)`. Each arm is tried once first.
+    pub fn simulate_ucb1<R: Rng>(&self, arms: Vec<DiscreteFiniteRandomExperiment<f64>>, rng: &mut R, n_rounds: usize) -> BanditResult {
+        let mut state = BanditState::new(&arms);
+        for t in 1..=n_rounds {
+            let arm = match state.untried() {
+                Some(arm) => arm,
+                None => argmax(state.estimates.iter()
+                    .zip(&state.pull_counts)
+                    .map(|(m, n)| m + (2.0 * (t as f64).ln() / *n as f64).sqrt())),
+            };
+            state.pull(rng, arm);
+        }
+        state.result()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn bandit_check() {
+        let mut rng = StdRng::seed_from_u64(107);
+        let arms = || -> Vec<DiscreteFiniteRandomExperiment<f64>> {
+            [0.2, 0.5, 0.8].iter()
+                .map(|p| DiscreteFiniteRandomExperiment::new(vec![0.0, 1.0], &[1.0 - p, *p]))
+                .collect()
+        };
+        let exp = DiscreteFiniteRandomExperiment::new(vec![()], &[1.0]);
+
+        let random = exp.simulate_epsilon_greedy(arms(), &mut rng, 10_000, 1.0);
+        let greedy = exp.simulate_epsilon_greedy(arms(), &mut rng, 10_000, 0.1);
+        let ucb = exp.simulate_ucb1(arms(), &mut rng, 10_000);
+
+        assert_eq!(greedy.pull_counts.iter().sum::<usize>(), 10_000);
+        assert!((random.cumulative_regret - 3000.0).abs() < 200.0);
+        assert!(greedy.cumulative_regret < random.cumulative_regret / 2.0);
+        assert!(ucb.cumulative_regret < random.cumulative_regret / 2.0);
+        assert!(greedy.pull_counts[2] > greedy.pull_counts[0] + greedy.pull_counts[1]);
+    }
+}
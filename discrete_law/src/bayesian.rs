@@ -0,0 +1,302 @@
+//! Bayesian models built on Dirichlet laws and processes.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::{DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment, SimulationResult};
+use crate::statistics::ln_gamma;
+
+/// Chinese restaurant process.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Seat `n` customers: customer `k` (from 0) joins a table with probability proportional
+    /// to its size or a new table with probability `alpha / (alpha + k)`.
+    /// Return the tables as lists of customer indices.
+    pub fn chinese_restaurant_process<R: Rng>(rng: &mut R, n: usize, alpha: f64) -> Vec<Vec<usize>> {
+        let mut tables: Vec<Vec<usize>> = Vec::new();
+        for customer in 0..n {
+            let mut weights: Vec<f64> = tables.iter().map(|t| t.len() as f64).collect();
+            weights.push(alpha);
+            let choice = DiscreteFiniteRandomExperiment::new((0..weights.len()).collect(), &weights).sample(rng);
+            if choice == tables.len() {
+                tables.push(vec![customer]);
+            } else {
+                tables[choice].push(customer);
+            }
+        }
+        tables
+    }
+
+    /// Expected number of tables after `n` customers, `sum_{k < n} alpha / (alpha + k)`,
+    /// about `alpha * ln(1 + n / alpha)`.
+    pub fn expected_number_of_tables(&self, n: usize, alpha: f64) -> f64 {
+        (0..n).map(|k| alpha / (alpha + k as f64)).sum()
+    }
+}
+
+/// Stick-breaking construction of a Dirichlet process.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Experiment over `0..n_breaks` whose weights break off Beta(1, `alpha`) proportions
+    /// `v_k` of the remaining stick, `w_k = v_k * prod_{j < k} (1 - v_j)`.
+    /// The last weight absorbs the remaining stick.
+    pub fn stick_breaking<R: Rng>(rng: &mut R, alpha: f64, n_breaks: usize) -> DiscreteFiniteRandomExperiment<usize> {
+        let mut remaining = 1.0;
+        let mut weights: Vec<f64> = (1..n_breaks)
+            .map(|_| {
+                // inverse cdf of Beta(1, alpha)
+                let v = 1.0 - rng.random::<f64>().powf(1.0 / alpha);
+                let w = v * remaining;
+                remaining -= w;
+                w
+            })
+            .collect();
+        weights.push(remaining);
+        DiscreteFiniteRandomExperiment::new((0..n_breaks).collect(), &weights)
+    }
+}
+
+/// All the vectors of `n_categories` counts adding up to `total`, in lexicographic order.
+fn compositions(total: usize, n_categories: usize) -> Vec<Vec<usize>> {
+    if n_categories == 1 {
+        return vec![vec![total]];
+    }
+    (0..=total)
+        .flat_map(|first| {
+            compositions(total - first, n_categories - 1).into_iter().map(move |mut rest| {
+                rest.insert(0, first);
+                rest
+            })
+        })
+        .collect()
+}
+
+/// Dirichlet-multinomial law: counts of `n_draws` multinomial draws whose probabilities follow a Dirichlet(`alpha`) law.
+impl DiscreteFiniteRandomExperiment<Vec<usize>> {
+    /// Probability of `counts`,
+    /// `n! Gamma(A) / Gamma(n + A) * prod Gamma(c_k + alpha_k) / (c_k! Gamma(alpha_k))` with `A = sum alpha_k`.
+    pub fn dirichlet_multinomial_pmf(alpha: &[f64], counts: &[usize]) -> f64 {
+        assert_eq!(alpha.len(), counts.len(), "one concentration per category is required");
+        let n: usize = counts.iter().sum();
+        let a: f64 = alpha.iter().sum();
+        let log_p = ln_gamma(n as f64 + 1.0) + ln_gamma(a) - ln_gamma(n as f64 + a)
+            + alpha.iter()
+                .zip(counts)
+                .map(|(ak, ck)| ln_gamma(*ck as f64 + ak) - ln_gamma(*ck as f64 + 1.0) - ln_gamma(*ak))
+                .sum::<f64>();
+        log_p.exp()
+    }
+
+    /// Exact law over all the count vectors of `n_draws` draws.
+    pub fn dirichlet_multinomial_experiment(alpha: &[f64], n_draws: usize) -> DiscreteFiniteRandomExperiment<Vec<usize>> {
+        let omega = compositions(n_draws, alpha.len());
+        let law: Vec<f64> = omega.iter().map(|c| Self::dirichlet_multinomial_pmf(alpha, c)).collect();
+        DiscreteFiniteRandomExperiment::new(omega, &law)
+    }
+
+    /// `repetitions` count vectors drawn with a Pólya urn: starting from weights `alpha`,
+    /// each draw adds 1 to the weight of the drawn category.
+    pub fn simulate_dirichlet_multinomial<R: Rng>(rng: &mut R, alpha: &[f64], n_draws: usize, repetitions: usize) -> Vec<Vec<usize>> {
+        (0..repetitions)
+            .map(|_| {
+                let mut counts = vec![0; alpha.len()];
+                for _ in 0..n_draws {
+                    let weights: Vec<f64> = alpha.iter().zip(&counts).map(|(a, c)| a + *c as f64).collect();
+                    let k = DiscreteFiniteRandomExperiment::new((0..alpha.len()).collect(), &weights).sample(rng);
+                    counts[k] += 1;
+                }
+                counts
+            })
+            .collect()
+    }
+}
+
+/// Empirical Bayes choice of a hyperparameter.
+impl<T: PartialEq> DiscreteFiniteRandomExperiment<T> {
+    /// Hyperparameter of `hyperparameter_grid` maximizing the marginal log-likelihood
+    /// `sum ln P(datum | prior_family(h))` of `data`, with its experiment.
+    pub fn empirical_bayes_estimate(data: &[T], prior_family: impl Fn(f64) -> DiscreteFiniteRandomExperiment<T>, hyperparameter_grid: &[f64]) -> (f64, DiscreteFiniteRandomExperiment<T>) {
+        hyperparameter_grid.iter()
+            .map(|h| {
+                let exp = prior_family(*h);
+                let log_likelihood: f64 = data.iter().map(|x| exp.probability_of(x).ln()).sum();
+                (*h, exp, log_likelihood)
+            })
+            .max_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(h, exp, _)| (h, exp))
+            .expect("the hyperparameter grid must not be empty")
+    }
+}
+
+/// Posterior predictive simulation.
+impl<T: PartialEq + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Draw `n` outcomes: each time pick an experiment of `posterior_experiments` with probability
+    /// proportional to `weights`, then draw from it. The outcomes of the result are those of all
+    /// the experiments, in order of appearance.
+    pub fn posterior_predictive<R: Rng>(posterior_experiments: Vec<DiscreteFiniteRandomExperiment<T>>, weights: &[f64], rng: &mut R, n: usize) -> SimulationResult<T> {
+        assert_eq!(posterior_experiments.len(), weights.len(), "one weight per experiment is required");
+        let mut omega: Vec<T> = Vec::new();
+        for exp in &posterior_experiments {
+            for o in &exp.omega {
+                if !omega.contains(o) {
+                    omega.push(o.clone());
+                }
+            }
+        }
+        // index in `omega` of each outcome of each experiment
+        let positions: Vec<Vec<usize>> = posterior_experiments.iter()
+            .map(|exp| exp.omega.iter().map(|o| omega.iter().position(|x| x == o).unwrap()).collect())
+            .collect();
+
+        let parameter = DiscreteFiniteDistribution::new(weights);
+        let mut counts = vec![0; omega.len()];
+        for _ in 0..n {
+            let k = parameter.sample(rng);
+            counts[positions[k][posterior_experiments[k].distribution.sample(rng)]] += 1;
+        }
+        SimulationResult { omega, counts }
+    }
+}
+
+/// Dirichlet-multinomial posterior predictive.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Law over `0..K` of the next draw after observing `data_counts` with a Dirichlet(`prior_alpha`) prior
+    /// on the multinomial probabilities: `(alpha_i + count_i) / (sum alpha + n)`.
+    pub fn predictive_distribution_dirichlet(data_counts: &[usize], prior_alpha: &[f64]) -> DiscreteFiniteRandomExperiment<usize> {
+        assert_eq!(data_counts.len(), prior_alpha.len(), "one concentration per category is required");
+        let total = prior_alpha.iter().sum::<f64>() + data_counts.iter().sum::<usize>() as f64;
+        let law: Vec<f64> = prior_alpha.iter().zip(data_counts).map(|(a, c)| (a + *c as f64) / total).collect();
+        DiscreteFiniteRandomExperiment::new((0..law.len()).collect(), &law)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{total_variation_distance, DiscreteFiniteDistribution};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn chinese_restaurant_check() {
+        let mut rng = StdRng::seed_from_u64(157);
+        let tables = DiscreteFiniteRandomExperiment::chinese_restaurant_process(&mut rng, 50, 2.0);
+        let mut customers: Vec<usize> = tables.iter().flatten().copied().collect();
+        customers.sort();
+        assert_eq!(customers, (0..50).collect::<Vec<_>>());
+        assert_eq!(tables[0][0], 0);
+
+        let unused = DiscreteFiniteRandomExperiment::new(vec![0], &[1.0]);
+        let harmonic: f64 = (1..=30).map(|k| 1.0 / k as f64).sum();
+        assert!((unused.expected_number_of_tables(30, 1.0) - harmonic).abs() < 1e-12);
+        assert!((unused.expected_number_of_tables(10_000, 5.0) / (5.0 * (1.0 + 2000f64).ln()) - 1.0).abs() < 0.05);
+
+        let mean = (0..2_000)
+            .map(|_| DiscreteFiniteRandomExperiment::chinese_restaurant_process(&mut rng, 30, 1.0).len() as f64)
+            .sum::<f64>() / 2_000.0;
+        assert!((mean - harmonic).abs() < 0.1);
+    }
+
+    #[test]
+    fn stick_breaking_check() {
+        let mut rng = StdRng::seed_from_u64(158);
+        let exp = DiscreteFiniteRandomExperiment::stick_breaking(&mut rng, 2.0, 20);
+        assert_eq!(exp.omega, (0..20).collect::<Vec<_>>());
+        assert!((exp.distribution.law.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        // the broken off weights, without the remaining stick, get closer to uniform as alpha grows
+        let distance_to_uniform = |rng: &mut StdRng, alpha: f64| {
+            (0..200)
+                .map(|_| {
+                    let law = DiscreteFiniteRandomExperiment::stick_breaking(rng, alpha, 11).distribution.law;
+                    total_variation_distance(&DiscreteFiniteDistribution::normalize(&law[..10]), &[0.1; 10])
+                })
+                .sum::<f64>() / 200.0
+        };
+        let (low, mid, high) = (distance_to_uniform(&mut rng, 0.5), distance_to_uniform(&mut rng, 5.0), distance_to_uniform(&mut rng, 1_000.0));
+        assert!(low > mid && mid > high);
+        let mut mean = [0.0; 10];
+        for _ in 0..500 {
+            let law = DiscreteFiniteRandomExperiment::stick_breaking(&mut rng, 1_000.0, 11).distribution.law;
+            for (m, w) in mean.iter_mut().zip(DiscreteFiniteDistribution::normalize(&law[..10])) {
+                *m += w / 500.0;
+            }
+        }
+        assert!(total_variation_distance(&mean, &[0.1; 10]) < 0.03);
+    }
+
+    #[test]
+    fn dirichlet_multinomial_check() {
+        let mut rng = StdRng::seed_from_u64(160);
+        let alpha = [0.5, 2.0, 1.5];
+        let n = 6;
+        let exp = DiscreteFiniteRandomExperiment::dirichlet_multinomial_experiment(&alpha, n);
+        assert_eq!(exp.omega.len(), 28);
+        assert!((exp.distribution.law.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // marginal of the first count: Beta-Binomial(n, 0.5, 3.5) by the ratio of consecutive terms
+        let (a, b) = (alpha[0], alpha[1] + alpha[2]);
+        let mut beta_binomial = vec![(0..n).map(|i| (b + i as f64) / (a + b + i as f64)).product::<f64>()];
+        for x in 0..n {
+            let xf = x as f64;
+            let next = beta_binomial[x] * (n - x) as f64 / (xf + 1.0) * (xf + a) / (n as f64 - xf - 1.0 + b);
+            beta_binomial.push(next);
+        }
+        let probabilities = exp.distribution.probabilities();
+        for (x, expected) in beta_binomial.iter().enumerate() {
+            let marginal: f64 = exp.omega.iter().zip(&probabilities).filter(|(c, _)| c[0] == x).map(|(_, p)| p).sum();
+            assert!((marginal - expected).abs() < 1e-9);
+        }
+
+        let draws = DiscreteFiniteRandomExperiment::simulate_dirichlet_multinomial(&mut rng, &alpha, n, 20_000);
+        assert!(draws.iter().all(|c| c.iter().sum::<usize>() == n));
+        for (x, expected) in beta_binomial.iter().enumerate() {
+            let frequency = draws.iter().filter(|c| c[0] == x).count() as f64 / 20_000.0;
+            assert!((frequency - expected).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn empirical_bayes_check() {
+        let mut rng = StdRng::seed_from_u64(171);
+        let bernoulli = |p: f64| DiscreteFiniteRandomExperiment::new(vec![false, true], &[1.0 - p, p]);
+        let data: Vec<bool> = (0..2_000).map(|_| bernoulli(0.7).sample(&mut rng)).collect();
+        assert!((bernoulli(0.7).probability_of(&true) - 0.7).abs() < 1e-12);
+
+        let grid: Vec<f64> = (1..100).map(|k| k as f64 / 100.0).collect();
+        let (p, exp) = DiscreteFiniteRandomExperiment::empirical_bayes_estimate(&data, bernoulli, &grid);
+        assert!((p - 0.7).abs() < 0.03);
+        assert!((exp.probability_of(&true) - p).abs() < 1e-12);
+        // the marginal MLE on the grid is the grid point closest to the observed frequency
+        let frequency = data.iter().filter(|x| **x).count() as f64 / data.len() as f64;
+        assert!((p - frequency).abs() <= 0.005 + 1e-12);
+    }
+
+    #[test]
+    fn posterior_predictive_check() {
+        let mut rng = StdRng::seed_from_u64(172);
+        let bernoulli = |p: f64| DiscreteFiniteRandomExperiment::new(vec![false, true], &[1.0 - p, p]);
+        let predictive = DiscreteFiniteRandomExperiment::posterior_predictive(vec![bernoulli(0.3), bernoulli(0.7)], &[1.0, 1.0], &mut rng, 100_000);
+        assert_eq!(predictive.omega, vec![false, true]);
+        assert_eq!(predictive.n(), 100_000);
+        assert!((predictive.frequencies()[1] - 0.5).abs() < 0.005);
+
+        let skewed = DiscreteFiniteRandomExperiment::posterior_predictive(vec![bernoulli(0.3), bernoulli(0.7)], &[3.0, 1.0], &mut rng, 100_000);
+        assert!((skewed.frequencies()[1] - 0.4).abs() < 0.005);
+    }
+
+    #[test]
+    fn dirichlet_predictive_check() {
+        let mut rng = StdRng::seed_from_u64(192);
+        let prior = DiscreteFiniteRandomExperiment::predictive_distribution_dirichlet(&[0, 0, 0, 0], &[1.0; 4]);
+        assert_eq!(prior.omega, vec![0, 1, 2, 3]);
+        assert_eq!(prior.distribution.probabilities(), vec![0.25; 4]);
+
+        let predictive = DiscreteFiniteRandomExperiment::predictive_distribution_dirichlet(&[3, 1, 0], &[1.0, 1.0, 2.0]);
+        assert_eq!(predictive.distribution.probabilities(), vec![0.5, 0.25, 0.25]);
+
+        let truth = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2, 3], &[0.1, 0.2, 0.3, 0.4]);
+        let counts = truth.simulate(&mut rng, 100_000).counts;
+        let mle: Vec<f64> = counts.iter().map(|c| *c as f64 / 100_000.0).collect();
+        let predictive = DiscreteFiniteRandomExperiment::predictive_distribution_dirichlet(&counts, &[1.0; 4]);
+        assert!(total_variation_distance(&predictive.distribution.probabilities(), &mle) < 1e-4);
+    }
+}
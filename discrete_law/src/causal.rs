@@ -0,0 +1,101 @@
+//! Causal inference: interventions and treatment effects.
+
+use rand::Rng;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Interventions on the variable whose law is `self`.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Hard intervention `do(X = omega[intervened_index])`: the same outcomes with all the mass on `intervened_index`.
+    pub fn causal_do_intervention(&self, intervened_index: usize) -> Self {
+        assert!(intervened_index < self.omega.len(), "intervened index out of the sample space");
+        let law: Vec<f64> = (0..self.omega.len()).map(|i| if i == intervened_index { 1.0 } else { 0.0 }).collect();
+        DiscreteFiniteRandomExperiment::new(self.omega.clone(), &law)
+    }
+
+    /// Average treatment effect `E[Y | do(T = 1)] - E[Y | do(T = 0)]` where `treated` and `base`
+    /// are the laws of the outcome under treatment and under control, and `Y = outcome_fn(outcome)`.
+    pub fn compute_ate<F: Fn(&T) -> f64>(base: &Self, treated: &Self, outcome_fn: F) -> f64 {
+        let mean = |exp: &Self| -> f64 {
+            exp.omega.iter()
+                .zip(exp.distribution.probabilities())
+                .map(|(o, p)| p * outcome_fn(o))
+                .sum()
+        };
+        mean(treated) - mean(base)
+    }
+}
+
+
+/// G-estimation of a structural equation.
+impl DiscreteFiniteRandomExperiment<f64> {
+    /// Effect `beta` of the structural model `Y = beta * A + h(L) + noise` from
+    /// `(treatment, outcome, propensity_score)` observations, the propensity score being `P(A = 1 | L)`.
+    ///
+    /// The estimating equation `sum (a_i - e_i) (y_i - beta a_i) = 0` is unbiased whatever `h`
+    /// since the residual treatment `a - e` is uncorrelated with the confounders, so
+    /// `beta = sum (a_i - e_i) y_i / sum (a_i - e_i) a_i`.
+    ///
+    /// `treatment_experiment` is the law of the treatment: every observed treatment must be one of its
+    /// outcomes of positive probability. The estimate being a closed form of the data, `rng` draws nothing.
+    pub fn g_estimate<R: Rng>(data: Vec<(f64, f64, f64)>, treatment_experiment: &Self, _rng: &mut R) -> f64 {
+        assert!(!data.is_empty(), "at least one observation is required");
+        assert!(data.iter().all(|(a, _, _)| treatment_experiment.probability_of(a) > 0.0),
+                "treatments must be outcomes of the treatment experiment");
+        let numerator: f64 = data.iter().map(|(a, y, e)| (a - e) * y).sum();
+        let denominator: f64 = data.iter().map(|(a, _, e)| (a - e) * a).sum();
+        numerator / denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand::distr::Distribution;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn do_operator_check() {
+        // outcome (treatment, recovered) with a structural law P(recovered | t) = 0.4 + 0.3 t
+        let structural = |t: f64| DiscreteFiniteRandomExperiment::new(
+            vec![(t, 0.0), (t, 1.0)],
+            &[0.6 - 0.3 * t, 0.4 + 0.3 * t],
+        );
+        let treatment = DiscreteFiniteRandomExperiment::new(vec![0.0, 1.0], &[0.8, 0.2]);
+        let treated = treatment.causal_do_intervention(1);
+        assert_eq!(treated.distribution.probabilities(), vec![0.0, 1.0]);
+        assert_eq!(treated.omega, treatment.omega);
+
+        let recovered = |o: &(f64, f64)| o.1;
+        let ate = DiscreteFiniteRandomExperiment::compute_ate(&structural(0.0), &structural(1.0), recovered);
+        assert!((ate - 0.3).abs() < 1e-12);
+        // a binary outcome equal to the treatment has an effect of 1
+        let identity = |o: &f64| *o;
+        assert!((DiscreteFiniteRandomExperiment::compute_ate(&treatment.causal_do_intervention(0), &treated, identity) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn g_estimation_check() {
+        let mut rng = StdRng::seed_from_u64(174);
+        let confounder = DiscreteFiniteRandomExperiment::new(vec![0.0, 1.0], &[0.5, 0.5]);
+        let noise = DiscreteFiniteRandomExperiment::new(vec![-1.0, 0.0, 1.0], &[1.0, 1.0, 1.0]);
+        let beta = 2.0;
+        let data: Vec<(f64, f64, f64)> = (0..50_000)
+            .map(|_| {
+                let l = confounder.sample(&mut rng);
+                let e = 0.2 + 0.6 * l;
+                let a = if rng.random::<f64>() < e { 1.0 } else { 0.0 };
+                (a, beta * a + 3.0 * l + noise.sample(&mut rng), e)
+            })
+            .collect();
+
+        let treated: Vec<f64> = data.iter().filter(|d| d.0 == 1.0).map(|d| d.1).collect();
+        let control: Vec<f64> = data.iter().filter(|d| d.0 == 0.0).map(|d| d.1).collect();
+        let naive = treated.iter().sum::<f64>() / treated.len() as f64 - control.iter().sum::<f64>() / control.len() as f64;
+        assert!(naive > beta + 1.0);
+        // standard deviation of the estimate is about 0.025
+        let treatment = DiscreteFiniteRandomExperiment::new(vec![0.0, 1.0], &[0.5, 0.5]);
+        assert!((DiscreteFiniteRandomExperiment::g_estimate(data, &treatment, &mut rng) - beta).abs() < 0.1);
+    }
+}
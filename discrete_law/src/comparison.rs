@@ -0,0 +1,247 @@
+//! Comparison of two experiments.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::{total_variation_distance, DiscreteFiniteRandomExperiment};
+
+/// Empirical orderings of paired draws `(X, Y)`.
+#[derive(Debug, Clone)]
+pub struct ComparisonResult {
+    pub p_greater: f64,
+    pub p_equal: f64,
+    pub p_less: f64,
+}
+
+/// Stochastic comparison of independent `X ~ self` and `Y ~ other` through `transform`.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Draw `n` independent pairs and count how often `transform(X)` is greater, equal or less than `transform(Y)`.
+    pub fn compare_with<R: Rng, U: PartialOrd, F: Fn(&T) -> U>(&self, other: &DiscreteFiniteRandomExperiment<T>, rng: &mut R, transform: F, n: usize) -> ComparisonResult {
+        let (mut greater, mut equal, mut less) = (0, 0, 0);
+        for _ in 0..n {
+            let x = transform(&self.omega[self.distribution.sample(rng)]);
+            let y = transform(&other.omega[other.distribution.sample(rng)]);
+            if x > y {
+                greater += 1;
+            } else if x == y {
+                equal += 1;
+            } else if x < y {
+                less += 1;
+            }
+        }
+        let n = n as f64;
+        ComparisonResult {
+            p_greater: greater as f64 / n,
+            p_equal: equal as f64 / n,
+            p_less: less as f64 / n,
+        }
+    }
+
+    /// Exact `P(transform(X) > transform(Y)) = sum p_i q_j` over the pairs where the transformed outcomes are ordered.
+    pub fn theoretical_p_greater<U: PartialOrd, F: Fn(&T) -> U>(&self, other: &Self, transform: F) -> f64 {
+        let p = self.distribution.probabilities();
+        let q = other.distribution.probabilities();
+        let xs: Vec<U> = self.omega.iter().map(&transform).collect();
+        let ys: Vec<U> = other.omega.iter().map(&transform).collect();
+        xs.iter().zip(&p)
+            .map(|(x, pi)| ys.iter().zip(&q).filter(|(y, _)| x > *y).map(|(_, qj)| pi * qj).sum::<f64>())
+            .sum()
+    }
+}
+
+
+/// Wasserstein-1 (earth mover) distance between the laws of two experiments for the ground cost `cost_fn`,
+/// the minimal cost of a coupling.
+///
+/// The transport problem is solved exactly as a minimum cost flow by successive shortest paths
+/// (Bellman-Ford on a dense residual graph), which suits a few dozen outcomes.
+pub fn wasserstein_1_distance<T, F: Fn(&T, &T) -> f64>(exp_a: &DiscreteFiniteRandomExperiment<T>, exp_b: &DiscreteFiniteRandomExperiment<T>, cost_fn: F) -> f64 {
+    const EPS: f64 = 1e-12;
+    let p = exp_a.distribution.probabilities();
+    let q = exp_b.distribution.probabilities();
+    let (m, k) = (p.len(), q.len());
+    // nodes: source, the outcomes of a, the outcomes of b, sink
+    let n = m + k + 2;
+    let (source, sink) = (0, n - 1);
+    let mut capacity = vec![vec![0.0; n]; n];
+    let mut cost = vec![vec![0.0; n]; n];
+    for i in 0..m {
+        capacity[source][1 + i] = p[i];
+        for j in 0..k {
+            let c = cost_fn(&exp_a.omega[i], &exp_b.omega[j]);
+            capacity[1 + i][1 + m + j] = f64::INFINITY;
+            cost[1 + i][1 + m + j] = c;
+            cost[1 + m + j][1 + i] = -c;
+        }
+    }
+    for j in 0..k {
+        capacity[1 + m + j][sink] = q[j];
+    }
+
+    let mut flow = 0.0;
+    let mut total = 0.0;
+    while flow < 1.0 - EPS {
+        // Bellman-Ford on the residual graph
+        let mut distance = vec![f64::INFINITY; n];
+        let mut parent = vec![usize::MAX; n];
+        distance[source] = 0.0;
+        for _ in 0..n {
+            let mut changed = false;
+            for u in 0..n {
+                if distance[u] == f64::INFINITY {
+                    continue;
+                }
+                for v in 0..n {
+                    if capacity[u][v] > EPS && distance[u] + cost[u][v] < distance[v] - EPS {
+                        distance[v] = distance[u] + cost[u][v];
+                        parent[v] = u;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        if parent[sink] == usize::MAX {
+            break;
+        }
+        let mut bottleneck = f64::INFINITY;
+        let mut v = sink;
+        while v != source {
+            bottleneck = bottleneck.min(capacity[parent[v]][v]);
+            v = parent[v];
+        }
+        let mut v = sink;
+        while v != source {
+            let u = parent[v];
+            capacity[u][v] -= bottleneck;
+            capacity[v][u] += bottleneck;
+            v = u;
+        }
+        flow += bottleneck;
+        total += bottleneck * distance[sink];
+    }
+    total
+}
+
+/// Distances between laws on the real line.
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Wasserstein-1 distance for the cost `|x - y|`: the integral of `|F_self - F_other|`.
+    pub fn wasserstein_1d(&self, other: &Self) -> f64 {
+        let mut points: Vec<(f64, f64)> = self.omega.iter()
+            .map(|x| x.clone().into())
+            .zip(self.distribution.probabilities())
+            .chain(other.omega.iter()
+                .map(|y| y.clone().into())
+                .zip(other.distribution.probabilities().into_iter().map(|q| -q)))
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut difference = 0.0;
+        let mut distance = 0.0;
+        for w in points.windows(2) {
+            difference += w[0].1;
+            distance += difference.abs() * (w[1].0 - w[0].0);
+        }
+        distance
+    }
+}
+
+/// Cohen's h effect size between two proportions, `2 asin(sqrt(p1)) - 2 asin(sqrt(p2))`.
+pub fn cohens_h(p1: f64, p2: f64) -> f64 {
+    2.0 * p1.sqrt().asin() - 2.0 * p2.sqrt().asin()
+}
+
+/// Conventional reading of the magnitude of an effect size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectSizeInterpretation {
+    /// Below 0.5.
+    Small,
+    /// From 0.5 to 0.8.
+    Medium,
+    /// From 0.8 to 1.2.
+    Large,
+    /// From 1.2.
+    VeryLarge,
+}
+
+/// Interpretation of `|es|` with Cohen's thresholds 0.5 and 0.8, and Sawilowsky's 1.2.
+pub fn interpret_effect_size(es: f64) -> EffectSizeInterpretation {
+    match es.abs() {
+        x if x < 0.5 => EffectSizeInterpretation::Small,
+        x if x < 0.8 => EffectSizeInterpretation::Medium,
+        x if x < 1.2 => EffectSizeInterpretation::Large,
+        _ => EffectSizeInterpretation::VeryLarge,
+    }
+}
+
+/// Effect sizes between the laws of two experiments.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Total variation distance between the laws, outcomes being matched by index.
+    pub fn total_variation_effect_size(exp_a: &Self, exp_b: &Self) -> f64 {
+        total_variation_distance(&exp_a.distribution.probabilities(), &exp_b.distribution.probabilities())
+    }
+
+    /// Earth mover's distance between the laws for the cost `cost_fn(i, j)` of moving mass from the
+    /// outcome index `i` of `exp_a` to the outcome index `j` of `exp_b` (see `wasserstein_1_distance`).
+    pub fn earth_movers_distance_effect_size(exp_a: &Self, exp_b: &Self, cost_fn: impl Fn(usize, usize) -> f64) -> f64 {
+        let indices = |exp: &Self| DiscreteFiniteRandomExperiment::new((0..exp.omega.len()).collect::<Vec<usize>>(), &exp.distribution.probabilities());
+        wasserstein_1_distance(&indices(exp_a), &indices(exp_b), |i, j| cost_fn(*i, *j))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn comparison_check() {
+        let mut rng = StdRng::seed_from_u64(129);
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+
+        assert!((dice.theoretical_p_greater(&dice, |x| *x) - 5.0 / 12.0).abs() < 1e-12);
+        assert!((dice.theoretical_p_greater(&dice, |x| -x) - 5.0 / 12.0).abs() < 1e-12);
+
+        let res = dice.compare_with(&dice, &mut rng, |x| *x, 100_000);
+        assert!((res.p_greater - 5.0 / 12.0).abs() < 0.01);
+        assert!((res.p_less - 5.0 / 12.0).abs() < 0.01);
+        assert!((res.p_equal - 1.0 / 6.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn wasserstein_check() {
+        let euclidean = |x: &f64, y: &f64| (x - y).abs();
+        let a = DiscreteFiniteRandomExperiment::new(vec![0.0, 1.0], &[0.7, 0.3]);
+        let b = DiscreteFiniteRandomExperiment::new(vec![0.0, 1.0], &[0.3, 0.7]);
+        assert!((wasserstein_1_distance(&a, &b, euclidean) - 0.4).abs() < 1e-12);
+        assert!((a.wasserstein_1d(&b) - 0.4).abs() < 1e-12);
+        assert!(wasserstein_1_distance(&a, &a, euclidean).abs() < 1e-12);
+
+        let c = DiscreteFiniteRandomExperiment::new(vec![0.0, 2.0, 5.0, 9.0], &[0.1, 0.4, 0.2, 0.3]);
+        let d = DiscreteFiniteRandomExperiment::new(vec![1.0, 3.0, 4.0], &[0.5, 0.25, 0.25]);
+        assert!((wasserstein_1_distance(&c, &d, euclidean) - c.wasserstein_1d(&d)).abs() < 1e-9);
+        // moving all the mass of a point mass costs the distance
+        let e = DiscreteFiniteRandomExperiment::new(vec![7.0], &[1.0]);
+        assert!((wasserstein_1_distance(&d, &e, |x, y| (x - y).powi(2)) - (0.5 * 36.0 + 0.25 * 16.0 + 0.25 * 9.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn effect_size_check() {
+        let h = cohens_h(0.3, 0.5);
+        assert!((h - (2.0 * 0.3f64.sqrt().asin() - 2.0 * 0.5f64.sqrt().asin())).abs() < 1e-12);
+        assert!((h + 0.4115168).abs() < 1e-6);
+        assert_eq!(interpret_effect_size(h), EffectSizeInterpretation::Small);
+        assert_eq!(interpret_effect_size(-0.6), EffectSizeInterpretation::Medium);
+        assert_eq!(interpret_effect_size(0.8), EffectSizeInterpretation::Large);
+        assert_eq!(interpret_effect_size(2.0), EffectSizeInterpretation::VeryLarge);
+
+        let a = DiscreteFiniteRandomExperiment::new(vec!['x', 'y', 'z'], &[0.5, 0.3, 0.2]);
+        let b = DiscreteFiniteRandomExperiment::new(vec!['x', 'y', 'z'], &[0.2, 0.3, 0.5]);
+        assert!((DiscreteFiniteRandomExperiment::total_variation_effect_size(&a, &b) - 0.3).abs() < 1e-12);
+        // moving 0.3 from the first to the last outcome, two steps away
+        let steps = |i: usize, j: usize| i.abs_diff(j) as f64;
+        assert!((DiscreteFiniteRandomExperiment::earth_movers_distance_effect_size(&a, &b, steps) - 0.6).abs() < 1e-9);
+    }
+}
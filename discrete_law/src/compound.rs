@@ -0,0 +1,128 @@
+//! Compound experiments: sums of independent draws.
+
+use rand::Rng;
+use rand::distr::Distribution;
+use std::collections::BTreeMap;
+use std::ops::Add;
+
+use crate::{DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment};
+
+/// Sums of `n` i.i.d. draws of an experiment with integer outcomes.
+impl<T: Into<i64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Empirical experiment of the sum of `n` draws, from `repetitions` sums.
+    pub fn simulate_sum_of_n<R: Rng>(&self, rng: &mut R, n: usize, repetitions: usize) -> DiscreteFiniteRandomExperiment<i64> {
+        let mut counts: BTreeMap<i64, usize> = BTreeMap::new();
+        for _ in 0..repetitions {
+            let sum: i64 = (0..n).map(|_| self.sample(rng).into()).sum();
+            *counts.entry(sum).or_insert(0) += 1;
+        }
+        DiscreteFiniteRandomExperiment::from_counts(counts)
+    }
+
+    /// Exact law of the sum of `n` draws by `n`-fold convolution.
+    pub fn theoretical_sum_distribution(&self, n: usize) -> DiscreteFiniteRandomExperiment<i64> {
+        let law: Vec<(i64, f64)> = self.omega.iter()
+            .map(|x| x.clone().into())
+            .zip(self.distribution.probabilities())
+            .collect();
+
+        let mut sums: BTreeMap<i64, f64> = BTreeMap::from([(0, 1.0)]);
+        for _ in 0..n {
+            let mut next: BTreeMap<i64, f64> = BTreeMap::new();
+            for (s, ps) in &sums {
+                for (x, px) in &law {
+                    *next.entry(s + x).or_insert(0.0) += ps * px;
+                }
+            }
+            sums = next;
+        }
+        let (omega, law): (Vec<i64>, Vec<f64>) = sums.into_iter().unzip();
+        DiscreteFiniteRandomExperiment::new(omega, &law)
+    }
+}
+
+
+impl DiscreteFiniteDistribution {
+    /// Law of `X + Y` for independent `X ~ self` and `Y ~ other` over outcome indices:
+    /// index `i + j` gets probability `p_i * q_j`.
+    pub fn convolve(&self, other: &Self) -> Self {
+        let p = self.probabilities();
+        let q = other.probabilities();
+        let mut law = vec![0.0; p.len() + q.len() - 1];
+        for (i, pi) in p.iter().enumerate() {
+            for (j, qj) in q.iter().enumerate() {
+                law[i + j] += pi * qj;
+            }
+        }
+        DiscreteFiniteDistribution::new(&law)
+    }
+}
+
+/// Sum of two independent experiments.
+impl<T: Add<Output = T> + Clone + PartialEq> DiscreteFiniteRandomExperiment<T> {
+    /// Experiment of `X + Y` for independent `X ~ self` and `Y ~ other`,
+    /// equal sums are merged in order of first appearance.
+    pub fn convolve_experiments(&self, other: &Self) -> Self {
+        let p = self.distribution.probabilities();
+        let q = other.distribution.probabilities();
+        let mut omega: Vec<T> = Vec::new();
+        let mut law: Vec<f64> = Vec::new();
+        for (x, px) in self.omega.iter().zip(&p) {
+            for (y, qy) in other.omega.iter().zip(&q) {
+                let sum = x.clone() + y.clone();
+                match omega.iter().position(|o| *o == sum) {
+                    Some(k) => law[k] += px * qy,
+                    None => {
+                        omega.push(sum);
+                        law.push(px * qy);
+                    }
+                }
+            }
+        }
+        DiscreteFiniteRandomExperiment::new(omega, &law)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn sum_of_n_check() {
+        let mut rng = StdRng::seed_from_u64(119);
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+
+        let two = dice.theoretical_sum_distribution(2);
+        assert_eq!(two.omega, (2..=12).collect::<Vec<i64>>());
+        for (s, p) in two.omega.iter().zip(two.distribution.probabilities()) {
+            assert!((p - (6 - (s - 7).abs()) as f64 / 36.0).abs() < 1e-12);
+        }
+
+        let sim = dice.simulate_sum_of_n(&mut rng, 2, 100_000);
+        assert_eq!(sim.omega, two.omega);
+        for (p, q) in sim.distribution.probabilities().iter().zip(two.distribution.probabilities()) {
+            assert!((p - q).abs() < 0.005);
+        }
+    }
+
+    #[test]
+    fn convolve_check() {
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let two = dice.theoretical_sum_distribution(2);
+
+        let indices = dice.distribution.convolve(&dice.distribution);
+        assert_eq!(indices.len(), 11);
+        for (p, q) in indices.probabilities().iter().zip(two.distribution.probabilities()) {
+            assert!((p - q).abs() < 1e-12);
+        }
+
+        let sum = dice.convolve_experiments(&dice);
+        let law = sum.distribution.probabilities();
+        for (s, q) in two.omega.iter().zip(two.distribution.probabilities()) {
+            let k = sum.omega.iter().position(|o| *o as i64 == *s).unwrap();
+            assert!((law[k] - q).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Sequential decisions under uncertainty solved by dynamic programming.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Result of `simulate_stochastic_knapsack`.
+#[derive(Debug, Clone)]
+pub struct KnapsackResult {
+    /// Mean value packed by the optimal policy over the simulations.
+    pub mean_value: f64,
+    /// Expected value of the optimal policy, from the dynamic programming table.
+    pub optimal_strategy_expected_value: f64,
+    /// Expected value of packing every item that fits.
+    pub greedy_strategy_expected_value: f64,
+}
+
+/// Online stochastic knapsack, the values of the items are i.i.d. draws of `self`.
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Items arrive one by one, item `i` has weight `weights[i]` and a value revealed at arrival,
+    /// it must be packed or discarded at once.
+    ///
+    /// `V(i, c) = E[max(V(i+1, c), v + V(i+1, c - w_i))]` gives the optimal policy:
+    /// the item is packed when `v + V(i+1, c - w_i) >= V(i+1, c)`.
+    pub fn simulate_stochastic_knapsack<R: Rng>(&self, rng: &mut R, weights: &[usize], capacity: usize, n_items: usize, repetitions: usize) -> KnapsackResult {
+        assert_eq!(weights.len(), n_items, "one weight per item is required");
+        let law: Vec<(f64, f64)> = self.omega.iter()
+            .map(|x| x.clone().into())
+            .zip(self.distribution.probabilities())
+            .collect();
+
+        let mut table = vec![vec![0.0f64; capacity + 1]; n_items + 1];
+        for i in (0..n_items).rev() {
+            for c in 0..=capacity {
+                let skip = table[i + 1][c];
+                table[i][c] = if weights[i] <= c {
+                    let keep = table[i + 1][c - weights[i]];
+                    law.iter().map(|(v, p)| p * skip.max(v + keep)).sum()
+                } else {
+                    skip
+                };
+            }
+        }
+
+        let mean = law.iter().map(|(v, p)| v * p).sum::<f64>();
+        let mut room = capacity;
+        let mut greedy = 0.0;
+        for w in weights {
+            if *w <= room {
+                room -= w;
+                greedy += mean;
+            }
+        }
+
+        let mut total = 0.0;
+        for _ in 0..repetitions {
+            let mut c = capacity;
+            for i in 0..n_items {
+                let v: f64 = self.sample(rng).into();
+                if weights[i] <= c && v + table[i + 1][c - weights[i]] >= table[i + 1][c] {
+                    total += v;
+                    c -= weights[i];
+                }
+            }
+        }
+
+        KnapsackResult {
+            mean_value: total / repetitions as f64,
+            optimal_strategy_expected_value: table[0][capacity],
+            greedy_strategy_expected_value: greedy,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn knapsack_check() {
+        let mut rng = StdRng::seed_from_u64(135);
+        let values = DiscreteFiniteRandomExperiment::new(vec![1.0, 10.0], &[0.5, 0.5]);
+        let res = values.simulate_stochastic_knapsack(&mut rng, &[1; 6], 2, 6, 20_000);
+
+        assert!((res.greedy_strategy_expected_value - 11.0).abs() < 1e-12);
+        assert!(res.optimal_strategy_expected_value > res.greedy_strategy_expected_value);
+        assert!((res.mean_value - res.optimal_strategy_expected_value).abs() < 0.1);
+
+        // a single slot at the last item: nothing to optimize
+        let res = values.simulate_stochastic_knapsack(&mut rng, &[1], 1, 1, 10);
+        assert!((res.optimal_strategy_expected_value - 5.5).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,577 @@
+//! Estimation of the law of an experiment from simulated samples.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::seq::SliceRandom;
+
+use crate::statistics::{ln_gamma, log_sum_exp, mean_and_variance, softmax};
+use crate::{total_variation_distance, DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment, SimulationResult, StatError};
+
+/// Maximum likelihood estimation of the law.
+///
+/// For a finite sample space the MLE is the vector of empirical frequencies.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// For each `n` in `ns`, draw `n` samples and return `(n, mle_law)`.
+    pub fn demonstrate_mle_convergence<R: Rng>(&self, rng: &mut R, ns: &[usize]) -> Vec<(usize, Vec<f64>)> {
+        ns.iter()
+            .map(|n| (*n, self.distribution.empirical_law(rng, *n)))
+            .collect()
+    }
+
+    /// Mean total variation distance between the true law and the MLE
+    /// computed from `n` samples, over `repetitions` experiments drawn with the thread rng.
+    pub fn mle_error_at_n(&self, n: usize, repetitions: usize) -> f64 {
+        let law = self.distribution.probabilities();
+        let mut rng = rand::rng();
+        let total: f64 = (0..repetitions)
+            .map(|_| total_variation_distance(&law, &self.distribution.empirical_law(&mut rng, n)))
+            .sum();
+        total / repetitions as f64
+    }
+}
+
+
+/// Wilson score interval for a proportion of `successes` among `n` trials at normal quantile `z`.
+pub fn wilson_interval(successes: usize, n: usize, z: f64) -> (f64, f64) {
+    let n = n as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    ((center - half).max(0.0), (center + half).min(1.0))
+}
+
+/// Result of `estimate_tail_probability`.
+#[derive(Debug, Clone)]
+pub struct TailEstimateResult {
+    /// Monte Carlo estimate (proportion of draws in the event).
+    pub estimate: f64,
+    /// 95% Wilson confidence interval.
+    pub confidence_interval: (f64, f64),
+}
+
+/// Probability of events by simulation.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Crude Monte Carlo estimate of `P(event)` from `n` draws.
+    pub fn estimate_tail_probability<R: Rng, F: Fn(&T) -> bool>(&self, rng: &mut R, event: F, n: usize) -> TailEstimateResult {
+        let hits = (0..n)
+            .filter(|_| event(&self.omega[self.distribution.sample(rng)]))
+            .count();
+        TailEstimateResult {
+            estimate: hits as f64 / n as f64,
+            confidence_interval: wilson_interval(hits, n, 1.96),
+        }
+    }
+
+    /// Importance sampling estimate of `P(event)` from `n` draws of `proposal`,
+    /// each draw in the event weighted by `p_i / q_i`.
+    /// `proposal` must have the same outcomes as `self`, in the same order.
+    pub fn importance_sampling_tail_estimate<R: Rng, F: Fn(&T) -> bool>(&self, proposal: &Self, rng: &mut R, event: F, n: usize) -> f64 {
+        assert_eq!(self.omega.len(), proposal.omega.len(), "proposal must have the same outcomes");
+        let p = self.distribution.probabilities();
+        let q = proposal.distribution.probabilities();
+        let total: f64 = (0..n)
+            .map(|_| proposal.distribution.sample(rng))
+            .filter(|i| event(&self.omega[*i]))
+            .map(|i| p[i] / q[i])
+            .sum();
+        total / n as f64
+    }
+}
+
+/// Coverage of confidence interval procedures.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Proportion of `n_simulations` samples of size `n` of `true_experiment` whose interval
+    /// `ci_procedure(sample)` contains `true_param`.
+    pub fn coverage_simulation<R: Rng, CI: Fn(&SimulationResult<T>) -> (f64, f64)>(true_experiment: &Self, rng: &mut R, true_param: f64, ci_procedure: CI, n: usize, n_simulations: usize) -> f64 {
+        let covered = (0..n_simulations)
+            .filter(|_| {
+                let (low, high) = ci_procedure(&true_experiment.simulate(rng, n));
+                low <= true_param && true_param <= high
+            })
+            .count();
+        covered as f64 / n_simulations as f64
+    }
+}
+
+/// Importance sampling.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Estimate `E[value_fn(X)]` for `X ~ self` from `n` draws of `proposal`,
+    /// as the mean of `value_fn(x) * p(x) / q(x)`.
+    /// Return the estimate and its variance (sample variance of the weighted values divided by `n`).
+    ///
+    /// `proposal` must have the same outcomes as `self`, in the same order, and give positive
+    /// probability to every outcome of positive probability under `self`.
+    pub fn importance_sampling_estimate<R: Rng, F: Fn(&T) -> f64>(&self, proposal: &Self, rng: &mut R, value_fn: F, n: usize) -> Result<(f64, f64), StatError> {
+        let p = self.distribution.probabilities();
+        let q = proposal.distribution.probabilities();
+        if p.len() != q.len() {
+            return Err(StatError::SupportMismatch(p.len(), q.len()));
+        }
+        if let Some(i) = (0..p.len()).find(|i| p[*i] > 0.0 && q[*i] == 0.0) {
+            return Err(StatError::UnsupportedOutcome(i));
+        }
+
+        let weighted: Vec<f64> = (0..n)
+            .map(|_| proposal.distribution.sample(rng))
+            .map(|i| value_fn(&self.omega[i]) * p[i] / q[i])
+            .collect();
+        let (mean, variance) = mean_and_variance(&weighted);
+        Ok((mean, variance / n as f64))
+    }
+}
+
+/// Fit a mixture of `n_components` Poisson laws to `data`, non negative integers, by expectation-maximization.
+/// Return the `(mixing_weight, component)` pairs by increasing Poisson mean, each component being its Poisson
+/// law restricted to the distinct values of `data` in order of first appearance.
+///
+/// The components are Poisson laws because a mixture of free laws on a finite set is not identifiable:
+/// any split of the empirical law has the same likelihood. The E-step computes the responsibilities
+/// `w_k P_k(x) / sum_l w_l P_l(x)`, the M-step sets each weight `w_k` to the mean responsibility of its
+/// component and each Poisson mean to the mean of the data weighted by these responsibilities.
+/// The responsibilities start from a smoothed split of the sorted distinct values into `n_components` blocks,
+/// where the block of a component gets twice the share of the others.
+/// Iterations stop after `max_iter` or when the log-likelihood improves by less than `tol`.
+pub fn em_fit_mixture<T: Eq + Clone + Hash + Into<f64>>(data: &[T], n_components: usize, max_iter: usize, tol: f64) -> Vec<(f64, DiscreteFiniteRandomExperiment<T>)> {
+    let mut index: HashMap<&T, usize> = HashMap::new();
+    let mut values: Vec<T> = Vec::new();
+    let mut counts: Vec<f64> = Vec::new();
+    for x in data {
+        let j = *index.entry(x).or_insert_with(|| {
+            values.push(x.clone());
+            counts.push(0.0);
+            values.len() - 1
+        });
+        counts[j] += 1.0;
+    }
+    let m = values.len();
+    assert!(n_components > 0 && n_components <= m, "need between 1 and {m} components");
+    let xs: Vec<f64> = values.iter().map(|x| x.clone().into()).collect();
+    assert!(xs.iter().all(|x| *x >= 0.0 && x.fract() == 0.0), "Poisson data must be non negative integers");
+    let n = data.len() as f64;
+    let log_poisson = |x: f64, mean: f64| {
+        let x_ln_mean = if x == 0.0 { 0.0 } else { x * mean.ln() };
+        x_ln_mean - mean - ln_gamma(x + 1.0)
+    };
+
+    // responsibilities[j][k] of component k for the value j
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|a, b| xs[*a].total_cmp(&xs[*b]));
+    let mut responsibilities = vec![vec![0.0; n_components]; m];
+    for (rank, j) in order.iter().enumerate() {
+        for (k, r) in responsibilities[*j].iter_mut().enumerate() {
+            *r = if rank * n_components / m == k { 2.0 } else { 1.0 } / (n_components + 1) as f64;
+        }
+    }
+    let mut weights = vec![0.0; n_components];
+    let mut means = vec![0.0; n_components];
+    let mut previous = f64::NEG_INFINITY;
+    for _ in 0..max_iter.max(1) {
+        // M-step
+        for k in 0..n_components {
+            let mass: f64 = (0..m).map(|j| counts[j] * responsibilities[j][k]).sum();
+            weights[k] = mass / n;
+            means[k] = (0..m).map(|j| counts[j] * responsibilities[j][k] * xs[j]).sum::<f64>() / mass;
+        }
+
+        // E-step
+        let mut log_likelihood = 0.0;
+        for j in 0..m {
+            let log_joint: Vec<f64> = (0..n_components).map(|k| weights[k].ln() + log_poisson(xs[j], means[k])).collect();
+            log_likelihood += counts[j] * log_sum_exp(&log_joint);
+            responsibilities[j] = softmax(&log_joint);
+        }
+        if log_likelihood - previous < tol {
+            break;
+        }
+        previous = log_likelihood;
+    }
+
+    let mut components: Vec<(f64, f64)> = weights.into_iter().zip(means).collect();
+    components.sort_by(|a, b| a.1.total_cmp(&b.1));
+    components.into_iter()
+        .map(|(w, mean)| {
+            let law = softmax(&xs.iter().map(|x| log_poisson(*x, mean)).collect::<Vec<f64>>());
+            (w, DiscreteFiniteRandomExperiment::new(values.clone(), &law))
+        })
+        .collect()
+}
+
+/// Fit a softmax law `p_i = exp(l_i) / sum_j exp(l_j)` over `0..n_outcomes` to `samples` by stochastic gradient
+/// descent on the negative log-likelihood, whose gradient for a sample `x` is `p_i - delta_(i, x)`.
+/// Each of the `n_epochs` epochs visits the samples in a random order, starting from uniform logits.
+pub fn sgd_fit<R: Rng>(samples: &[usize], n_outcomes: usize, learning_rate: f64, n_epochs: usize, rng: &mut R) -> DiscreteFiniteRandomExperiment<usize> {
+    assert!(samples.iter().all(|x| *x < n_outcomes), "samples must be outcome indices below {n_outcomes}");
+    let mut logits = vec![0.0; n_outcomes];
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    for _ in 0..n_epochs {
+        order.shuffle(rng);
+        for k in &order {
+            let p = softmax(&logits);
+            for (i, l) in logits.iter_mut().enumerate() {
+                let delta = if i == samples[*k] { 1.0 } else { 0.0 };
+                *l += learning_rate * (delta - p[i]);
+            }
+        }
+    }
+    DiscreteFiniteRandomExperiment::new((0..n_outcomes).collect(), &softmax(&logits))
+}
+
+/// Sensitivity of a simulated statistic to the law.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Finite difference gradient of `statistic` of `n` draws: for each index `i`, `p_i` is increased
+    /// by `delta`, the law renormalized and `(statistic(perturbed) - statistic(base)) / delta` computed.
+    /// All the simulations share the same uniform draws (common random numbers) mapped by the quantile functions.
+    pub fn sensitivity_analysis<R: Rng, S: Fn(&SimulationResult<T>) -> f64>(&self, rng: &mut R, statistic: S, delta: f64, n: usize) -> Vec<f64> {
+        let uniforms: Vec<f64> = (0..n).map(|_| rng.random()).collect();
+        let run = |distribution: &DiscreteFiniteDistribution| {
+            let mut counts = vec![0; self.omega.len()];
+            for u in &uniforms {
+                counts[distribution.quantile(*u)] += 1;
+            }
+            statistic(&SimulationResult { omega: self.omega.clone(), counts })
+        };
+        let law = self.distribution.probabilities();
+        let base = run(&self.distribution);
+        (0..law.len())
+            .map(|i| {
+                let mut perturbed = law.clone();
+                perturbed[i] += delta;
+                let perturbed = DiscreteFiniteDistribution::new(&DiscreteFiniteDistribution::normalize(&perturbed));
+                (run(&perturbed) - base) / delta
+            })
+            .collect()
+    }
+}
+
+/// Information criterion used by `model_select`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelSelectionCriterion {
+    /// Akaike: `-2 ln L + 2 k`.
+    Aic,
+    /// Bayesian (Schwarz): `-2 ln L + k ln n`.
+    Bic,
+}
+
+/// Score `-2 ln L + penalty` of a model with `n_parameters` free parameters fitted to `n` observations.
+fn criterion_score(log_likelihood: f64, n_parameters: usize, n: usize, criterion: ModelSelectionCriterion) -> f64 {
+    let k = n_parameters as f64;
+    let penalty = match criterion {
+        ModelSelectionCriterion::Aic => 2.0 * k,
+        ModelSelectionCriterion::Bic => k * (n as f64).ln(),
+    };
+    -2.0 * log_likelihood + penalty
+}
+
+/// Index of the lowest score, ties being broken uniformly at random.
+fn select_lowest<R: Rng>(scores: &[f64], rng: &mut R) -> usize {
+    let best = scores.iter().copied().fold(f64::INFINITY, f64::min);
+    let ties: Vec<usize> = (0..scores.len()).filter(|i| scores[*i] == best).collect();
+    ties[rng.random_range(0..ties.len())]
+}
+
+/// Select among `candidate_experiments` the law of `data` with the lowest criterion, `k` being the
+/// number of free parameters of a candidate (its number of outcomes minus one) and `L` the likelihood of
+/// the data, an observation outside the outcomes having probability 0. Return the index of the selected
+/// candidate and its score; ties are broken uniformly at random.
+pub fn model_select<R: Rng>(data: &[usize], candidate_experiments: Vec<DiscreteFiniteRandomExperiment<usize>>, criterion: ModelSelectionCriterion, rng: &mut R) -> (usize, f64) {
+    assert!(!candidate_experiments.is_empty(), "at least one candidate is required");
+    let scores: Vec<f64> = candidate_experiments.iter()
+        .map(|candidate| {
+            let log_likelihood: f64 = data.iter().map(|x| candidate.probability_of(x).ln()).sum();
+            criterion_score(log_likelihood, candidate.omega.len().saturating_sub(1), data.len(), criterion)
+        })
+        .collect();
+    let selected = select_lowest(&scores, rng);
+    (selected, scores[selected])
+}
+
+/// Truncated geometric law `p_i` proportional to `r^i` on `0..k` whose mean is `mean`, by bisection on `ln r`.
+/// The law being an exponential family in `i`, it is the maximum likelihood fit to samples of mean `mean`.
+fn truncated_geometric_law(k: usize, mean: f64) -> Vec<f64> {
+    let law = |log_ratio: f64| softmax(&(0..k).map(|i| log_ratio * i as f64).collect::<Vec<f64>>());
+    let law_mean = |law: &[f64]| law.iter().enumerate().map(|(i, p)| i as f64 * p).sum::<f64>();
+    let (mut low, mut high) = (-50.0, 50.0);
+    for _ in 0..100 {
+        let middle = 0.5 * (low + high);
+        if law_mean(&law(middle)) < mean {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    law(0.5 * (low + high))
+}
+
+/// Select the number of outcomes `k` in `1..=max_k` of the law of `data`, candidate `k` being the truncated
+/// geometric law `p_i` proportional to `r^i` on `0..k` with the ratio `r > 0` fitted by maximum likelihood,
+/// one free parameter (none for `k = 1`). A `k` not above the largest observation has likelihood 0.
+/// Return the selected `k` and its score; ties are broken uniformly at random.
+pub fn select_number_of_outcomes<R: Rng>(data: &[usize], max_k: usize, criterion: ModelSelectionCriterion, rng: &mut R) -> (usize, f64) {
+    assert!(max_k > 0, "at least one candidate is required");
+    let mean = data.iter().sum::<usize>() as f64 / data.len() as f64;
+    let scores: Vec<f64> = (1..=max_k)
+        .map(|k| {
+            let law = truncated_geometric_law(k, mean);
+            let log_likelihood: f64 = data.iter().map(|x| law.get(*x).map_or(f64::NEG_INFINITY, |p| p.ln())).sum();
+            criterion_score(log_likelihood, k.min(2) - 1, data.len(), criterion)
+        })
+        .collect();
+    let selected = select_lowest(&scores, rng);
+    (selected + 1, scores[selected])
+}
+
+/// Log-likelihood `sum count_i ln p_i` of the counts of the outcome indices, an index outside the outcomes
+/// having probability 0.
+fn counts_log_likelihood(data_counts: &[usize], law: &[f64]) -> f64 {
+    data_counts.iter()
+        .enumerate()
+        .filter(|(_, c)| **c > 0)
+        .map(|(i, c)| *c as f64 * law.get(i).map_or(f64::NEG_INFINITY, |p| p.ln()))
+        .sum()
+}
+
+/// Two part minimum description length (in nats) of the counts `data_counts[i]` of the outcome index `i`
+/// under the law of `experiment`: `-ln L + k / 2 * ln n` with `k` its number of outcomes minus one.
+pub fn mdl_score(data_counts: &[usize], experiment: &DiscreteFiniteRandomExperiment<usize>) -> f64 {
+    let n = data_counts.iter().sum::<usize>() as f64;
+    let k = experiment.omega.len().saturating_sub(1) as f64;
+    -counts_log_likelihood(data_counts, &experiment.distribution.probabilities()) + k / 2.0 * n.ln()
+}
+
+/// Parametric complexity `C(K, n) = sum_x P(x | mle(x))` of the multinomial model with `K` outcomes over
+/// samples of size `n`, by the recurrence `C(K + 2, n) = C(K + 1, n) + n / K * C(K, n)` (Kontkanen and Myllymaki).
+fn multinomial_complexity(n_outcomes: usize, n: usize) -> f64 {
+    if n_outcomes <= 1 {
+        return 1.0;
+    }
+    let nf = n as f64;
+    let p_ln_p = |h: f64| if h == 0.0 { 0.0 } else { h * (h / nf).ln() };
+    let binary: f64 = (0..=n)
+        .map(|h| {
+            let h = h as f64;
+            (ln_gamma(nf + 1.0) - ln_gamma(h + 1.0) - ln_gamma(nf - h + 1.0) + p_ln_p(h) + p_ln_p(nf - h)).exp()
+        })
+        .sum();
+    let (mut previous, mut current) = (1.0, binary);
+    for k in 1..n_outcomes - 1 {
+        (previous, current) = (current, current + nf / k as f64 * previous);
+    }
+    current
+}
+
+/// Normalized maximum likelihood code length (in nats) of the counts in the model of all the laws on the
+/// outcomes of `experiment`: `-ln P(x | mle(x)) + ln C(K, n)` with `C` the parametric complexity.
+/// Only the number of outcomes `K` of `experiment` matters, its law is replaced by the maximum likelihood one.
+pub fn normalized_maximum_likelihood_score(data_counts: &[usize], experiment: &DiscreteFiniteRandomExperiment<usize>) -> f64 {
+    let n: usize = data_counts.iter().sum();
+    let mle: Vec<f64> = (0..experiment.omega.len())
+        .map(|i| data_counts.get(i).map_or(0.0, |c| *c as f64 / n as f64))
+        .collect();
+    -counts_log_likelihood(data_counts, &mle) + multinomial_complexity(experiment.omega.len(), n).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn mle_convergence_check() {
+        let mut rng = StdRng::seed_from_u64(103);
+        let exp = DiscreteFiniteRandomExperiment::new(vec!['a', 'b', 'c'], &[1.0, 2.0, 7.0]);
+
+        let mles = exp.demonstrate_mle_convergence(&mut rng, &[10, 100_000]);
+        assert_eq!(mles[0].0, 10);
+        assert!((mles[0].1.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((mles[1].1[2] - 0.7).abs() < 0.01);
+
+        let errors: Vec<f64> = [10, 100, 1000]
+            .iter()
+            .map(|n| exp.mle_error_at_n(*n, 200))
+            .collect();
+        assert!(errors[0] > errors[1]);
+        assert!(errors[1] > errors[2]);
+    }
+
+    #[test]
+    fn tail_probability_check() {
+        let mut rng = StdRng::seed_from_u64(117);
+        let rare = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.999, 0.001]);
+        let proposal = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5]);
+
+        let res = rare.estimate_tail_probability(&mut rng, |x| *x == 1, 100_000);
+        assert!(res.confidence_interval.0 <= 0.001 && 0.001 <= res.confidence_interval.1);
+
+        let cv = |estimates: &[f64]| {
+            let (m, v) = mean_and_variance(estimates);
+            v.sqrt() / m
+        };
+        let crude: Vec<f64> = (0..200)
+            .map(|_| rare.estimate_tail_probability(&mut rng, |x| *x == 1, 1_000).estimate)
+            .collect();
+        let is: Vec<f64> = (0..200)
+            .map(|_| rare.importance_sampling_tail_estimate(&proposal, &mut rng, |x| *x == 1, 1_000))
+            .collect();
+        assert!(cv(&is) < cv(&crude) / 10.0);
+    }
+
+    #[test]
+    fn importance_sampling_check() {
+        let mut rng = StdRng::seed_from_u64(136);
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let high = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0, 1.0, 1.0, 1.0, 4.0, 4.0]);
+        let at_least_5 = |x: &i32| if *x >= 5 { 1.0 } else { 0.0 };
+
+        let (crude, crude_var) = dice.importance_sampling_estimate(&dice, &mut rng, at_least_5, 10_000).unwrap();
+        let (is, is_var) = dice.importance_sampling_estimate(&high, &mut rng, at_least_5, 10_000).unwrap();
+        assert!((crude - 1.0 / 3.0).abs() < 0.02);
+        assert!((is - 1.0 / 3.0).abs() < 0.02);
+        assert!(is_var < crude_var);
+
+        let missing = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(dice.importance_sampling_estimate(&missing, &mut rng, at_least_5, 10).unwrap_err(),
+                   StatError::UnsupportedOutcome(1));
+    }
+
+    #[test]
+    fn em_mixture_check() {
+        let mut rng = StdRng::seed_from_u64(145);
+        let poisson = |mean: f64| {
+            let law: Vec<f64> = (0..40).map(|x| (x as f64 * mean.ln() - mean - ln_gamma(x as f64 + 1.0)).exp()).collect();
+            DiscreteFiniteRandomExperiment::new((0..40).collect::<Vec<i32>>(), &law)
+        };
+        let (low, high) = (poisson(2.0), poisson(9.0));
+        let data: Vec<i32> = (0..20_000)
+            .map(|_| if rng.random::<f64>() < 0.4 { low.sample(&mut rng) } else { high.sample(&mut rng) })
+            .collect();
+
+        let mixture = em_fit_mixture(&data, 2, 500, 1e-9);
+        assert_eq!(mixture.len(), 2);
+        assert!((mixture[0].0 + mixture[1].0 - 1.0).abs() < 1e-12);
+        assert!((mixture[0].0 - 0.4).abs() < 0.02);
+
+        // each component is the true Poisson law restricted to the observed values
+        for ((_, fitted), truth) in mixture.iter().zip([&low, &high]) {
+            let restricted: Vec<f64> = fitted.omega.iter().map(|x| truth.probability_of(x)).collect();
+            let restricted = DiscreteFiniteDistribution::normalize(&restricted);
+            assert!(total_variation_distance(&fitted.distribution.probabilities(), &restricted) < 0.02);
+        }
+        assert!((mixture[0].1.expected_value() - 2.0).abs() < 0.1);
+        assert!((mixture[1].1.expected_value() - 9.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn sensitivity_check() {
+        let mut rng = StdRng::seed_from_u64(175);
+        let exp = DiscreteFiniteRandomExperiment::new(vec!['a', 'b', 'c'], &[0.1, 0.3, 0.6]);
+        let frequency_of_a = |sim: &SimulationResult<char>| sim.frequencies()[0];
+
+        // finite difference of p_0 -> (p_0 + delta) / (1 + delta) is about 0.82, of p_0 -> p_0 / (1 + delta) about -0.09
+        let gradient = exp.sensitivity_analysis(&mut rng, frequency_of_a, 0.1, 200_000);
+        assert_eq!(gradient.len(), 3);
+        assert!(gradient[0] > 0.0);
+        assert!((gradient[0] - (0.2 / 1.1 - 0.1) / 0.1).abs() < 0.1);
+        assert!((gradient[1] - (0.1 / 1.1 - 0.1) / 0.1).abs() < 0.05);
+        assert!((gradient[2] - (0.1 / 1.1 - 0.1) / 0.1).abs() < 0.05);
+    }
+
+    #[test]
+    fn sgd_fit_check() {
+        let mut rng = StdRng::seed_from_u64(188);
+        let law = [0.2, 0.5, 0.3];
+        let exp = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &law);
+        let samples: Vec<usize> = (0..10_000).map(|_| exp.distribution.sample(&mut rng)).collect();
+
+        let fitted = sgd_fit(&samples, 3, 0.001, 5, &mut rng);
+        assert_eq!(fitted.omega, vec![0, 1, 2]);
+        let p = fitted.distribution.probabilities();
+        assert!(p.iter().zip(law).all(|(p, q)| (p - q).abs() < 0.02));
+    }
+
+    #[test]
+    #[should_panic(expected = "samples must be outcome indices below 3")]
+    fn sgd_fit_out_of_range() {
+        sgd_fit(&[0, 3], 3, 0.001, 1, &mut StdRng::seed_from_u64(188));
+    }
+
+    #[test]
+    fn model_selection_check() {
+        let mut rng = StdRng::seed_from_u64(190);
+        let exp = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[0.5, 0.3, 0.2]);
+        let data: Vec<usize> = (0..500).map(|_| exp.sample(&mut rng)).collect();
+
+        // the geometric fit on 0..3 has the sample mean, with ratio r: (r + 2 r^2) / (1 + r + r^2) = mean
+        let mean = data.iter().sum::<usize>() as f64 / data.len() as f64;
+        let law = truncated_geometric_law(3, mean);
+        let ratio = law[1] / law[0];
+        assert!((law[2] / law[1] - ratio).abs() < 1e-9);
+        assert!(((ratio + 2.0 * ratio * ratio) / (1.0 + ratio + ratio * ratio) - mean).abs() < 1e-9);
+        let log_likelihood: f64 = data.iter().map(|x| law[*x].ln()).sum();
+
+        // fewer outcomes miss observations, more outcomes waste probability on unobserved ones
+        for (criterion, penalty) in [(ModelSelectionCriterion::Aic, 2.0), (ModelSelectionCriterion::Bic, 500f64.ln())] {
+            let (k, score) = select_number_of_outcomes(&data, 6, criterion, &mut rng);
+            assert_eq!(k, 3);
+            assert!((score - (-2.0 * log_likelihood + penalty)).abs() < 1e-6);
+        }
+
+        // the true law beats a uniform one, with the same number of parameters
+        let candidates = vec![
+            DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[1.0; 3]),
+            DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[0.5, 0.3, 0.2]),
+        ];
+        let (selected, score) = model_select(&data, candidates, ModelSelectionCriterion::Aic, &mut rng);
+        assert_eq!(selected, 1);
+        let log_likelihood: f64 = data.iter().map(|x| [0.5f64, 0.3, 0.2][*x].ln()).sum();
+        assert!((score - (-2.0 * log_likelihood + 4.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn mdl_check() {
+        let mut rng = StdRng::seed_from_u64(191);
+        // C(2, 2) = 1 + 1/4 + 1/4 + 1 = 5/2 and C(3, 2) = C(2, 2) + 2 C(1, 2)
+        assert!((multinomial_complexity(2, 2) - 2.5).abs() < 1e-9);
+        assert!((multinomial_complexity(3, 2) - 4.5).abs() < 1e-9);
+        assert_eq!(multinomial_complexity(1, 10), 1.0);
+
+        for law in [vec![0.6, 0.4], vec![0.5, 0.3, 0.2], vec![0.4, 0.3, 0.2, 0.1]] {
+            let exp = DiscreteFiniteRandomExperiment::new((0..law.len()).collect(), &law);
+            let counts = exp.simulate(&mut rng, 500).counts;
+            let models: Vec<DiscreteFiniteRandomExperiment<usize>> = (1..=6)
+                .map(|k| {
+                    let weights: Vec<f64> = (0..k).map(|i| counts.get(i).map_or(0.0, |c| *c as f64 + 1e-12)).collect();
+                    DiscreteFiniteRandomExperiment::new((0..k).collect(), &weights)
+                })
+                .collect();
+            let mdl: Vec<f64> = models.iter().map(|m| mdl_score(&counts, m)).collect();
+            let nml: Vec<f64> = models.iter().map(|m| normalized_maximum_likelihood_score(&counts, m)).collect();
+
+            let best = |scores: &[f64]| (0..scores.len()).min_by(|a, b| scores[*a].total_cmp(&scores[*b])).unwrap();
+            assert_eq!(best(&mdl), law.len() - 1);
+            assert_eq!(best(&nml), law.len() - 1);
+            for i in 0..6 {
+                for j in 0..6 {
+                    assert_eq!(mdl[i] < mdl[j], nml[i] < nml[j]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn coverage_check() {
+        let mut rng = StdRng::seed_from_u64(198);
+        let bernoulli = DiscreteFiniteRandomExperiment::new(vec![false, true], &[0.6, 0.4]);
+        let wilson = |sim: &SimulationResult<bool>| wilson_interval(sim.counts[1], sim.n(), 1.96);
+        let coverage = DiscreteFiniteRandomExperiment::coverage_simulation(&bernoulli, &mut rng, 0.4, wilson, 100, 10_000);
+        assert!((0.93..=0.97).contains(&coverage), "{coverage}");
+
+        // an interval reduced to the estimate almost never covers
+        let point = |sim: &SimulationResult<bool>| (sim.frequencies()[1], sim.frequencies()[1]);
+        assert!(DiscreteFiniteRandomExperiment::coverage_simulation(&bernoulli, &mut rng, 0.4, point, 100, 1_000) < 0.15);
+    }
+}
@@ -0,0 +1,180 @@
+//! Game theory: mixed strategies of matrix games and auctions.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Nash equilibrium of the zero-sum game paying `payoff_matrix[i][j]` to the row player
+/// when the row player plays `i` and the column player `j`.
+/// Return the row strategy, the column strategy and the value of the game.
+///
+/// After shifting the payoffs to be positive, the column strategy solves the linear program
+/// `max sum y_j` with `A y <= 1, y >= 0` by the simplex method (Bland's rule); the row strategy
+/// comes from its dual.
+pub fn find_nash_equilibrium(payoff_matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<f64>, f64) {
+    let m = payoff_matrix.len();
+    let n = payoff_matrix[0].len();
+    let min = payoff_matrix.iter().flatten().copied().fold(f64::INFINITY, f64::min);
+    let shift = 1.0 - min;
+
+    // tableau rows: constraints [A | I | 1], last row: objective [-1 | 0 | 0]
+    let width = n + m + 1;
+    let mut tableau: Vec<Vec<f64>> = payoff_matrix.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut line: Vec<f64> = row.iter().map(|a| a + shift).collect();
+            line.extend((0..m).map(|k| if k == i { 1.0 } else { 0.0 }));
+            line.push(1.0);
+            line
+        })
+        .collect();
+    let mut objective = vec![-1.0; n];
+    objective.extend(vec![0.0; m + 1]);
+    tableau.push(objective);
+    let mut basis: Vec<usize> = (n..n + m).collect();
+
+    const EPS: f64 = 1e-12;
+    while let Some(entering) = (0..n + m).find(|j| tableau[m][*j] < -EPS) {
+        let leaving = (0..m)
+            .filter(|i| tableau[*i][entering] > EPS)
+            .min_by(|a, b| {
+                let ra = tableau[*a][width - 1] / tableau[*a][entering];
+                let rb = tableau[*b][width - 1] / tableau[*b][entering];
+                ra.total_cmp(&rb).then(basis[*a].cmp(&basis[*b]))
+            })
+            .expect("the linear program of a matrix game is bounded");
+        let pivot = tableau[leaving][entering];
+        tableau[leaving].iter_mut().for_each(|x| *x /= pivot);
+        let pivot_row = tableau[leaving].clone();
+        for (i, row) in tableau.iter_mut().enumerate() {
+            if i != leaving {
+                let factor = row[entering];
+                row.iter_mut().zip(&pivot_row).for_each(|(x, p)| *x -= factor * p);
+            }
+        }
+        basis[leaving] = entering;
+    }
+
+    let mut y = vec![0.0; n];
+    for (i, b) in basis.iter().enumerate() {
+        if *b < n {
+            y[*b] = tableau[i][width - 1];
+        }
+    }
+    let x: Vec<f64> = (0..m).map(|i| tableau[m][n + i]).collect();
+    let total = tableau[m][width - 1];
+    (
+        x.iter().map(|xi| xi / total).collect(),
+        y.iter().map(|yj| yj / total).collect(),
+        1.0 / total - shift,
+    )
+}
+
+/// Repeated matrix games between mixed strategies over action indices.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Average payoff to the row player over `n_rounds` rounds where each player draws its action from its strategy.
+    pub fn simulate_repeated_game<R: Rng>(payoff_matrix: &[Vec<f64>], row_strategy: &Self, col_strategy: &Self, rng: &mut R, n_rounds: usize) -> f64 {
+        (0..n_rounds)
+            .map(|_| payoff_matrix[row_strategy.sample(rng)][col_strategy.sample(rng)])
+            .sum::<f64>() / n_rounds as f64
+    }
+}
+
+
+/// Result of `simulate_second_price_auction`.
+#[derive(Debug, Clone)]
+pub struct AuctionResult {
+    /// Mean highest valuation.
+    pub mean_winner_value: f64,
+    /// Mean second highest valuation, paid by the winner.
+    pub mean_price_paid: f64,
+    /// Mean revenue of the seller, the price paid in a second-price auction.
+    pub mean_seller_revenue: f64,
+    /// Whether no shaded or inflated bid ever gave a bidder more than bidding its valuation.
+    pub truthfulness_verified: bool,
+}
+
+/// Bid multipliers tried against truthful bidding.
+const BID_DEVIATIONS: [f64; 4] = [0.5, 0.8, 1.2, 1.5];
+
+/// Second-price (Vickrey) auction with i.i.d. private valuations drawn from `self`.
+impl<T: Ord + Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Run `repetitions` auctions between `n_bidders` truthful bidders. In each auction the utility
+    /// of the first bidder is also computed for bids equal to its valuation times each of `BID_DEVIATIONS`,
+    /// the others bidding truthfully (a bid tied with the highest other bid loses).
+    pub fn simulate_second_price_auction<R: Rng>(&self, rng: &mut R, n_bidders: usize, repetitions: usize) -> AuctionResult {
+        assert!(n_bidders >= 2, "an auction needs at least two bidders");
+        let (mut winner_value, mut price) = (0.0, 0.0);
+        let mut truthfulness_verified = true;
+        for _ in 0..repetitions {
+            let mut valuations: Vec<T> = (0..n_bidders).map(|_| self.sample(rng)).collect();
+            let own: f64 = valuations[0].clone().into();
+            let highest_other: f64 = valuations[1..].iter().max().unwrap().clone().into();
+            let utility = |bid: f64| if bid > highest_other { own - highest_other } else { 0.0 };
+            truthfulness_verified &= BID_DEVIATIONS.iter().all(|d| utility(own * d) <= utility(own));
+
+            valuations.sort();
+            winner_value += valuations[n_bidders - 1].clone().into();
+            price += valuations[n_bidders - 2].clone().into();
+        }
+        let n = repetitions as f64;
+        AuctionResult {
+            mean_winner_value: winner_value / n,
+            mean_price_paid: price / n,
+            mean_seller_revenue: price / n,
+            truthfulness_verified,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn nash_check() {
+        let mut rng = StdRng::seed_from_u64(161);
+        let rock_paper_scissors = vec![vec![0.0, -1.0, 1.0], vec![1.0, 0.0, -1.0], vec![-1.0, 1.0, 0.0]];
+        let (x, y, value) = find_nash_equilibrium(&rock_paper_scissors);
+        assert!(x.iter().chain(&y).all(|p| (p - 1.0 / 3.0).abs() < 1e-9));
+        assert!(value.abs() < 1e-9);
+
+        let game = vec![vec![3.0, -1.0], vec![-2.0, 1.0]];
+        let (x, y, value) = find_nash_equilibrium(&game);
+        assert!((x[0] - 3.0 / 7.0).abs() < 1e-9);
+        assert!((y[0] - 2.0 / 7.0).abs() < 1e-9);
+        assert!((value - 1.0 / 7.0).abs() < 1e-9);
+
+        let row = DiscreteFiniteRandomExperiment::new(vec![0, 1], &x);
+        let col = DiscreteFiniteRandomExperiment::new(vec![0, 1], &y);
+        let average = DiscreteFiniteRandomExperiment::simulate_repeated_game(&game, &row, &col, &mut rng, 200_000);
+        assert!((average - value).abs() < 0.02);
+
+        // saddle point
+        let (x, y, value) = find_nash_equilibrium(&[vec![2.0, 3.0], vec![1.0, 0.0]]);
+        assert!((x[0] - 1.0).abs() < 1e-12 && (y[0] - 1.0).abs() < 1e-12);
+        assert!((value - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn second_price_auction_check() {
+        let mut rng = StdRng::seed_from_u64(162);
+        let die = DiscreteFiniteRandomExperiment::new(vec![1u8, 2, 3, 4, 5, 6], &[1.0; 6]);
+
+        let two = die.simulate_second_price_auction(&mut rng, 2, 50_000);
+        // E[min] and E[max] of two dice
+        assert!((two.mean_price_paid - 91.0 / 36.0).abs() < 0.03);
+        assert!((two.mean_winner_value - 161.0 / 36.0).abs() < 0.03);
+        assert_eq!(two.mean_price_paid, two.mean_seller_revenue);
+        assert!(two.truthfulness_verified);
+
+        let revenues: Vec<f64> = [2, 4, 8]
+            .iter()
+            .map(|n| die.simulate_second_price_auction(&mut rng, *n, 20_000).mean_seller_revenue)
+            .collect();
+        assert!(revenues.windows(2).all(|w| w[0] < w[1]));
+    }
+}
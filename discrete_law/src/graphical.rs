@@ -0,0 +1,401 @@
+//! Inference in pairwise Markov random fields whose node potentials are experiments.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::statistics::softmax;
+use crate::{DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment};
+
+/// Exact inference on chains.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Marginal law of each node of the chain with joint law proportional to
+    /// `prod_i p_i(x_i) prod_i chain_potentials[i][x_i][x_(i+1)]`, `p_i` being the law of `node_experiments[i]`.
+    ///
+    /// Sum-product messages are passed forward then backward along the chain (normalized to avoid underflow),
+    /// the marginal of a node being proportional to its law times its two incoming messages.
+    pub fn belief_propagation(chain_potentials: &[Vec<Vec<f64>>], node_experiments: &[DiscreteFiniteRandomExperiment<usize>]) -> Vec<Vec<f64>> {
+        let n = node_experiments.len();
+        assert_eq!(chain_potentials.len() + 1, n.max(1), "one potential per pair of adjacent nodes is required");
+        let laws: Vec<Vec<f64>> = node_experiments.iter().map(|e| e.distribution.probabilities()).collect();
+
+        // forward[i] is the message from node i - 1 to node i, backward[i] from node i + 1 to node i
+        let mut forward: Vec<Vec<f64>> = laws.iter().map(|l| vec![1.0; l.len()]).collect();
+        let mut backward = forward.clone();
+        for i in 1..n {
+            let message: Vec<f64> = (0..laws[i].len())
+                .map(|t| (0..laws[i - 1].len()).map(|s| forward[i - 1][s] * laws[i - 1][s] * chain_potentials[i - 1][s][t]).sum())
+                .collect();
+            forward[i] = DiscreteFiniteDistribution::normalize(&message);
+        }
+        for i in (0..n.saturating_sub(1)).rev() {
+            let message: Vec<f64> = (0..laws[i].len())
+                .map(|s| (0..laws[i + 1].len()).map(|t| backward[i + 1][t] * laws[i + 1][t] * chain_potentials[i][s][t]).sum())
+                .collect();
+            backward[i] = DiscreteFiniteDistribution::normalize(&message);
+        }
+
+        (0..n)
+            .map(|i| {
+                let belief: Vec<f64> = (0..laws[i].len()).map(|s| laws[i][s] * forward[i][s] * backward[i][s]).collect();
+                DiscreteFiniteDistribution::normalize(&belief)
+            })
+            .collect()
+    }
+}
+
+/// Result of `LoopyBP::run`.
+#[derive(Debug, Clone)]
+pub struct LoopyBPResult {
+    /// Approximate marginal law of each node.
+    pub beliefs: Vec<Vec<f64>>,
+    /// Largest change of a message at each iteration.
+    pub residuals: Vec<f64>,
+    /// Whether the last residual is below the tolerance.
+    pub converged: bool,
+}
+
+/// Loopy belief propagation: sum-product messages iterated on a graph with cycles,
+/// whose beliefs approximate the marginals (and are exact on trees).
+#[derive(Debug)]
+pub struct LoopyBP {
+    n_nodes: usize,
+    /// `(u, v, potential)` with `potential[x_u][x_v]` the compatibility of the states of `u` and `v`.
+    edges: Vec<(usize, usize, Vec<Vec<f64>>)>,
+    laws: Vec<Vec<f64>>,
+    /// Messages of each edge, from `u` to `v` then from `v` to `u`.
+    messages: Vec<[Vec<f64>; 2]>,
+}
+
+impl LoopyBP {
+    /// Model with joint law proportional to `prod_u p_u(x_u) prod_(u, v) potential[x_u][x_v]`, all messages uniform.
+    pub fn new(n_nodes: usize, edges: Vec<(usize, usize, Vec<Vec<f64>>)>, node_experiments: Vec<DiscreteFiniteRandomExperiment<usize>>) -> Self {
+        assert_eq!(node_experiments.len(), n_nodes, "one experiment per node is required");
+        let laws: Vec<Vec<f64>> = node_experiments.iter().map(|e| e.distribution.probabilities()).collect();
+        let messages = edges.iter()
+            .map(|(u, v, _)| [vec![1.0 / laws[*v].len() as f64; laws[*v].len()], vec![1.0 / laws[*u].len() as f64; laws[*u].len()]])
+            .collect();
+        LoopyBP { n_nodes, edges, laws, messages }
+    }
+
+    /// Law of `node` times its incoming messages, except the one along edge `excluded`.
+    fn incoming(&self, node: usize, excluded: Option<usize>) -> Vec<f64> {
+        let mut product = self.laws[node].clone();
+        for (e, (u, v, _)) in self.edges.iter().enumerate() {
+            if Some(e) == excluded {
+                continue;
+            }
+            let message = if *v == node {
+                &self.messages[e][0]
+            } else if *u == node {
+                &self.messages[e][1]
+            } else {
+                continue;
+            };
+            product.iter_mut().zip(message).for_each(|(p, m)| *p *= m);
+        }
+        product
+    }
+
+    /// Update all the messages from the current ones (parallel schedule) until the largest change
+    /// is below `tol` or `max_iter` iterations, then compute the beliefs.
+    pub fn run(&mut self, max_iter: usize, tol: f64) -> LoopyBPResult {
+        let mut residuals = Vec::new();
+        for _ in 0..max_iter {
+            let updated: Vec<[Vec<f64>; 2]> = self.edges.iter()
+                .enumerate()
+                .map(|(e, (u, v, potential))| {
+                    let from_u = self.incoming(*u, Some(e));
+                    let from_v = self.incoming(*v, Some(e));
+                    let to_v: Vec<f64> = (0..from_v.len())
+                        .map(|t| (0..from_u.len()).map(|s| from_u[s] * potential[s][t]).sum())
+                        .collect();
+                    let to_u: Vec<f64> = (0..from_u.len())
+                        .map(|s| (0..from_v.len()).map(|t| from_v[t] * potential[s][t]).sum())
+                        .collect();
+                    [DiscreteFiniteDistribution::normalize(&to_v), DiscreteFiniteDistribution::normalize(&to_u)]
+                })
+                .collect();
+            let residual = updated.iter()
+                .zip(&self.messages)
+                .flat_map(|(new, old)| new.iter().flatten().zip(old.iter().flatten()))
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            self.messages = updated;
+            residuals.push(residual);
+            if residual < tol {
+                break;
+            }
+        }
+        let beliefs = (0..self.n_nodes)
+            .map(|node| DiscreteFiniteDistribution::normalize(&self.incoming(node, None)))
+            .collect();
+        let converged = residuals.last().is_some_and(|r| *r < tol);
+        LoopyBPResult { beliefs, residuals, converged }
+    }
+}
+
+/// Mean field approximation.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Product law `prod_i q_i(x_i)` closest in `KL(q || p)` to the joint law `p(x)` proportional to
+    /// `exp(joint_log_potential(x))`, `x_i` ranging over the outcomes of `variable_experiments[i]`
+    /// whose laws are the starting point.
+    ///
+    /// Coordinate ascent: each factor is in turn replaced by `q_i(x_i)` proportional to `exp(E_q[log potential | x_i])`,
+    /// until no probability moves by more than `tol` during a sweep. The expectations enumerate all the
+    /// configurations, which limits this to small models.
+    pub fn mean_field_fit(joint_log_potential: impl Fn(&[usize]) -> f64, variable_experiments: Vec<DiscreteFiniteRandomExperiment<usize>>, max_iter: usize, tol: f64) -> Vec<DiscreteFiniteRandomExperiment<usize>> {
+        let sizes: Vec<usize> = variable_experiments.iter().map(|e| e.omega.len()).collect();
+        let n_configurations: usize = sizes.iter().product();
+        let mut factors: Vec<Vec<f64>> = variable_experiments.iter().map(|e| e.distribution.probabilities()).collect();
+        let mut indices = vec![0; sizes.len()];
+        let mut x = vec![0; sizes.len()];
+
+        for _ in 0..max_iter {
+            let mut change: f64 = 0.0;
+            for i in 0..sizes.len() {
+                // expected log potential given x_i, weighted by the other factors
+                let mut expected = vec![0.0; sizes[i]];
+                for configuration in 0..n_configurations {
+                    let mut rest = configuration;
+                    for (j, size) in sizes.iter().enumerate() {
+                        indices[j] = rest % size;
+                        x[j] = variable_experiments[j].omega[indices[j]];
+                        rest /= size;
+                    }
+                    let weight: f64 = (0..sizes.len()).filter(|j| *j != i).map(|j| factors[j][indices[j]]).product();
+                    if weight > 0.0 {
+                        expected[indices[i]] += weight * joint_log_potential(&x);
+                    }
+                }
+                let updated = softmax(&expected);
+                change = factors[i].iter().zip(&updated).map(|(a, b)| (a - b).abs()).fold(change, f64::max);
+                factors[i] = updated;
+            }
+            if change < tol {
+                break;
+            }
+        }
+        variable_experiments.into_iter()
+            .zip(&factors)
+            .map(|(e, q)| DiscreteFiniteRandomExperiment::new(e.omega, q))
+            .collect()
+    }
+}
+
+/// Stochastic variational inference.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Product law `prod_i q_i(x_i)` maximizing the evidence lower bound `E_q[log_joint(x) - ln q(x)]`,
+    /// `x_i` being the outcome of `variational_family[i]` (as a `f64`) whose laws are the starting point.
+    ///
+    /// Each factor is a softmax of logits. At each of the `max_iter` iterations the gradient is estimated
+    /// by REINFORCE from `n_samples_per_iter` draws of `q`: the mean of `(f(x) - b) * (delta_(x_i, j) - q_i(j))`
+    /// with `f(x) = log_joint(x) - ln q(x)` and the baseline `b` the mean of `f`, then the logits take
+    /// a `learning_rate` step up.
+    pub fn variational_inference<R: Rng>(log_joint: impl Fn(&[f64]) -> f64, variational_family: Vec<DiscreteFiniteRandomExperiment<usize>>, n_samples_per_iter: usize, max_iter: usize, learning_rate: f64, rng: &mut R) -> Vec<DiscreteFiniteRandomExperiment<usize>> {
+        let mut logits: Vec<Vec<f64>> = variational_family.iter()
+            .map(|e| e.distribution.probabilities().iter().map(|p| p.ln()).collect())
+            .collect();
+
+        for _ in 0..max_iter {
+            let factors: Vec<DiscreteFiniteDistribution> = logits.iter().map(|l| DiscreteFiniteDistribution::new(&softmax(l))).collect();
+            let laws: Vec<Vec<f64>> = factors.iter().map(|f| f.probabilities()).collect();
+            let draws: Vec<Vec<usize>> = (0..n_samples_per_iter)
+                .map(|_| factors.iter().map(|f| f.sample(rng)).collect())
+                .collect();
+            let scores: Vec<f64> = draws.iter()
+                .map(|indices| {
+                    let x: Vec<f64> = indices.iter().zip(&variational_family).map(|(j, e)| e.omega[*j] as f64).collect();
+                    let ln_q: f64 = indices.iter().zip(&laws).map(|(j, q)| q[*j].ln()).sum();
+                    log_joint(&x) - ln_q
+                })
+                .collect();
+            let baseline = scores.iter().sum::<f64>() / n_samples_per_iter as f64;
+
+            for (i, l) in logits.iter_mut().enumerate() {
+                for (j, logit) in l.iter_mut().enumerate() {
+                    let gradient = draws.iter()
+                        .zip(&scores)
+                        .map(|(indices, f)| (f - baseline) * (if indices[i] == j { 1.0 } else { 0.0 } - laws[i][j]))
+                        .sum::<f64>() / n_samples_per_iter as f64;
+                    *logit += learning_rate * gradient;
+                }
+            }
+        }
+        variational_family.into_iter()
+            .zip(&logits)
+            .map(|(e, l)| DiscreteFiniteRandomExperiment::new(e.omega, &softmax(l)))
+            .collect()
+    }
+}
+
+/// Result of `simulate_ising`.
+#[derive(Debug, Clone)]
+pub struct IsingResult {
+    /// Spins (-1 or 1) after the last update.
+    pub spins: Vec<i8>,
+    /// Mean spin after each update.
+    pub magnetization: Vec<f64>,
+    /// Bethe lattice estimate `J / atanh(1 / (d - 1))` of the critical temperature for the mean degree `d`,
+    /// zero when `d <= 2` (no transition).
+    pub critical_temperature: f64,
+}
+
+/// Ising model.
+impl DiscreteFiniteRandomExperiment<i8> {
+    /// Glauber dynamics of the Ising model with energy `-coupling * sum_(i, j) s_i s_j` over `edges`,
+    /// from uniform random spins: at each of the `n_steps` steps a uniform spin is redrawn from its law
+    /// given its neighbours, `P(s_i = 1) = 1 / (1 + exp(-2 h_i / temperature))` with `h_i = coupling * sum_j s_j`.
+    pub fn simulate_ising<R: Rng>(rng: &mut R, n_spins: usize, edges: &[(usize, usize)], coupling: f64, temperature: f64, n_steps: usize) -> IsingResult {
+        let mut neighbours = vec![Vec::new(); n_spins];
+        for (a, b) in edges {
+            neighbours[*a].push(*b);
+            neighbours[*b].push(*a);
+        }
+        let fair = DiscreteFiniteRandomExperiment::new(vec![-1i8, 1], &[0.5, 0.5]);
+        let mut spins: Vec<i8> = (0..n_spins).map(|_| fair.sample(rng)).collect();
+        let mut total: i64 = spins.iter().map(|s| *s as i64).sum();
+
+        let mut magnetization = Vec::with_capacity(n_steps);
+        for _ in 0..n_steps {
+            let i = rng.random_range(0..n_spins);
+            let field = coupling * neighbours[i].iter().map(|j| spins[*j] as f64).sum::<f64>();
+            let up = 1.0 / (1.0 + (-2.0 * field / temperature).exp());
+            let spin = DiscreteFiniteRandomExperiment::new(vec![-1i8, 1], &[1.0 - up, up]).sample(rng);
+            total += (spin - spins[i]) as i64;
+            spins[i] = spin;
+            magnetization.push(total as f64 / n_spins as f64);
+        }
+
+        let degree = 2.0 * edges.len() as f64 / n_spins as f64;
+        let critical_temperature = if degree > 2.0 { coupling / (1.0 / (degree - 1.0)).atanh() } else { 0.0 };
+        IsingResult { spins, magnetization, critical_temperature }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use crate::graphs::grid_edges;
+
+    #[test]
+    fn chain_belief_propagation_check() {
+        let nodes = vec![
+            DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.3, 0.7]),
+            DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[0.2, 0.5, 0.3]),
+            DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.6, 0.4]),
+        ];
+        let potentials = vec![
+            vec![vec![2.0, 1.0, 0.5], vec![0.5, 1.0, 3.0]],
+            vec![vec![1.0, 4.0], vec![2.0, 1.0], vec![0.1, 1.0]],
+        ];
+        let marginals = DiscreteFiniteRandomExperiment::belief_propagation(&potentials, &nodes);
+
+        // brute force over the 12 configurations
+        let laws: Vec<Vec<f64>> = nodes.iter().map(|e| e.distribution.probabilities()).collect();
+        let mut exact = vec![vec![0.0; 2], vec![0.0; 3], vec![0.0; 2]];
+        for a in 0..2 {
+            for b in 0..3 {
+                for c in 0..2 {
+                    let weight = laws[0][a] * laws[1][b] * laws[2][c] * potentials[0][a][b] * potentials[1][b][c];
+                    exact[0][a] += weight;
+                    exact[1][b] += weight;
+                    exact[2][c] += weight;
+                }
+            }
+        }
+        for (marginal, weights) in marginals.iter().zip(&exact) {
+            let expected = DiscreteFiniteDistribution::normalize(weights);
+            assert!(marginal.iter().zip(&expected).all(|(m, e)| (m - e).abs() < 1e-12));
+        }
+
+        let single = DiscreteFiniteRandomExperiment::belief_propagation(&[], &nodes[..1]);
+        assert!((single[0][1] - 0.7).abs() < 1e-12);
+    }
+
+    #[test]
+    fn loopy_belief_propagation_check() {
+        let laws = [[0.3, 0.7], [0.5, 0.5], [0.6, 0.4], [0.8, 0.2]];
+        let nodes: Vec<_> = laws.iter().map(|l| DiscreteFiniteRandomExperiment::new(vec![0, 1], l)).collect();
+        let attractive = vec![vec![1.5, 1.0], vec![1.0, 1.5]];
+        let edges: Vec<_> = (0..4).map(|u| (u, (u + 1) % 4, attractive.clone())).collect();
+
+        let mut bp = LoopyBP::new(4, edges, nodes);
+        let result = bp.run(100, 1e-10);
+        assert!(result.converged);
+        assert!(result.residuals.len() < 100);
+        assert!(result.residuals.last().unwrap() < &1e-10);
+
+        // brute force over the 16 configurations
+        let mut exact = vec![vec![0.0; 2]; 4];
+        for config in 0..16 {
+            let x: Vec<usize> = (0..4).map(|i| (config >> i) & 1).collect();
+            let weight: f64 = (0..4).map(|u| laws[u][x[u]] * attractive[x[u]][x[(u + 1) % 4]]).product();
+            for u in 0..4 {
+                exact[u][x[u]] += weight;
+            }
+        }
+        for (belief, weights) in result.beliefs.iter().zip(&exact) {
+            let expected = DiscreteFiniteDistribution::normalize(weights);
+            assert!((belief[0] - expected[0]).abs() < 0.005);
+        }
+    }
+
+    #[test]
+    fn mean_field_check() {
+        // two Ising spins s = 2 x - 1 with fields h and coupling j
+        let spin = |x: usize| 2.0 * x as f64 - 1.0;
+        let ising = |h: [f64; 2], j: f64| move |x: &[usize]| h[0] * spin(x[0]) + h[1] * spin(x[1]) + j * spin(x[0]) * spin(x[1]);
+        let start = || (0..2).map(|_| DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5])).collect();
+
+        // without coupling the joint law is a product and the mean field is exact
+        let h = [0.4, -0.7];
+        let fitted = DiscreteFiniteRandomExperiment::mean_field_fit(ising(h, 0.0), start(), 100, 1e-12);
+        for (q, h) in fitted.iter().zip(h) {
+            let exact = h.exp() / (h.exp() + (-h).exp());
+            assert!((q.distribution.probabilities()[1] - exact).abs() < 1e-9);
+        }
+
+        // with coupling the magnetizations m_i = 2 q_i(1) - 1 solve m_i = tanh(h_i + j m_(other))
+        let h = [0.3, 0.1];
+        let fitted = DiscreteFiniteRandomExperiment::mean_field_fit(ising(h, 0.5), start(), 200, 1e-12);
+        let m: Vec<f64> = fitted.iter().map(|q| 2.0 * q.distribution.probabilities()[1] - 1.0).collect();
+        assert!((m[0] - (h[0] + 0.5 * m[1]).tanh()).abs() < 1e-9);
+        assert!((m[1] - (h[1] + 0.5 * m[0]).tanh()).abs() < 1e-9);
+        assert_eq!(fitted[0].omega, vec![0, 1]);
+    }
+
+    #[test]
+    fn ising_check() {
+        let mut rng = StdRng::seed_from_u64(185);
+        let edges = grid_edges(10, 10);
+        let last_mean = |result: &IsingResult| result.magnetization[50_000..].iter().sum::<f64>() / 50_000.0;
+
+        let hot = DiscreteFiniteRandomExperiment::simulate_ising(&mut rng, 100, &edges, 1.0, 10.0, 100_000);
+        assert_eq!(hot.magnetization.len(), 100_000);
+        assert!(last_mean(&hot).abs() < 0.1);
+        // exact critical temperature of the infinite square lattice is 2.269
+        assert!(hot.critical_temperature > 2.0 && hot.critical_temperature < 2.885);
+
+        let cold = DiscreteFiniteRandomExperiment::simulate_ising(&mut rng, 100, &edges, 1.0, 1.0, 100_000);
+        assert!(last_mean(&cold).abs() > 0.9);
+        let final_magnetization = cold.spins.iter().map(|s| *s as f64).sum::<f64>() / 100.0;
+        assert_eq!(final_magnetization, *cold.magnetization.last().unwrap());
+    }
+
+    #[test]
+    fn variational_inference_check() {
+        let mut rng = StdRng::seed_from_u64(193);
+        // independent Bernoulli(0.8) and Bernoulli(0.3): the ELBO is maximal at the true marginals
+        let bernoulli = |p: f64, x: f64| if x == 1.0 { p.ln() } else { (1.0 - p).ln() };
+        let log_joint = |x: &[f64]| bernoulli(0.8, x[0]) + bernoulli(0.3, x[1]);
+        let family = (0..2).map(|_| DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5])).collect();
+
+        let fitted = DiscreteFiniteRandomExperiment::variational_inference(log_joint, family, 100, 2_000, 0.1, &mut rng);
+        assert_eq!(fitted.len(), 2);
+        assert!((fitted[0].distribution.probabilities()[1] - 0.8).abs() < 0.03);
+        assert!((fitted[1].distribution.probabilities()[1] - 0.3).abs() < 0.03);
+    }
+}
@@ -0,0 +1,264 @@
+//! Random graphs: edges or nodes kept by Bernoulli experiments.
+
+use std::collections::HashSet;
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Disjoint sets over `0..n` with path halving and union by size.
+pub(crate) struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(n: usize) -> Self {
+        UnionFind { parent: (0..n).collect(), size: vec![1; n] }
+    }
+
+    pub(crate) fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    pub(crate) fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+    }
+
+    pub(crate) fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+}
+
+/// Result of the percolation simulations.
+#[derive(Debug, Clone)]
+pub struct PercolationResult {
+    /// Mean size of the largest component divided by the number of nodes.
+    pub mean_largest_component_fraction: f64,
+    /// Probability that a component crosses the grid, i.e. holds a node of its left and right columns.
+    pub probability_spanning_component: f64,
+}
+
+/// Edges of the `width` x `height` square grid, node `(x, y)` being `y * width + x`.
+pub fn grid_edges(width: usize, height: usize) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let node = y * width + x;
+            if x + 1 < width {
+                edges.push((node, node + 1));
+            }
+            if y + 1 < height {
+                edges.push((node, node + width));
+            }
+        }
+    }
+    edges
+}
+
+fn bernoulli(p: f64) -> DiscreteFiniteRandomExperiment<bool> {
+    DiscreteFiniteRandomExperiment::new(vec![true, false], &[p, 1.0 - p])
+}
+
+/// Side of the square grid formed by `n_nodes` nodes.
+fn grid_side(n_nodes: usize) -> usize {
+    let side = n_nodes.isqrt();
+    assert!(side * side == n_nodes, "the nodes must form a square grid");
+    side
+}
+
+/// Size of the largest component among the `present` nodes, and whether one of them holds
+/// a present node of the left (`x = 0`) and of the right (`x = side - 1`) columns of the grid.
+fn percolation_run(components: &mut UnionFind, present: &[bool], side: usize) -> (usize, bool) {
+    let largest = (0..present.len()).filter(|x| present[*x]).map(|x| components.component_size(x)).max().unwrap_or(0);
+    let left: HashSet<usize> = (0..side).map(|y| y * side).filter(|x| present[*x]).map(|x| components.find(x)).collect();
+    let spanning = (0..side).map(|y| y * side + side - 1).any(|x| present[x] && left.contains(&components.find(x)));
+    (largest, spanning)
+}
+
+fn percolation_result(runs: &[(usize, bool)], n_nodes: usize) -> PercolationResult {
+    let r = runs.len() as f64;
+    PercolationResult {
+        mean_largest_component_fraction: runs.iter().map(|(l, _)| *l as f64 / n_nodes as f64).sum::<f64>() / r,
+        probability_spanning_component: runs.iter().filter(|(_, s)| *s).count() as f64 / r,
+    }
+}
+
+/// Percolation on a graph given by its edges. The nodes are the cells of a square grid numbered as in
+/// `grid_edges`, which only matters for spanning: a component spans when it crosses from left to right.
+impl DiscreteFiniteRandomExperiment<bool> {
+    /// Bond percolation: each edge is kept with probability `p`.
+    pub fn simulate_bond_percolation<R: Rng>(rng: &mut R, n_nodes: usize, edges: &[(usize, usize)], p: f64, repetitions: usize) -> PercolationResult {
+        let side = grid_side(n_nodes);
+        let open = bernoulli(p);
+        let present = vec![true; n_nodes];
+        let runs: Vec<(usize, bool)> = (0..repetitions)
+            .map(|_| {
+                let mut components = UnionFind::new(n_nodes);
+                for (a, b) in edges {
+                    if open.sample(rng) {
+                        components.union(*a, *b);
+                    }
+                }
+                percolation_run(&mut components, &present, side)
+            })
+            .collect();
+        percolation_result(&runs, n_nodes)
+    }
+
+    /// Site percolation: each node is kept with probability `p`, with the edges between kept nodes.
+    pub fn simulate_site_percolation<R: Rng>(rng: &mut R, n_nodes: usize, edges: &[(usize, usize)], p: f64, repetitions: usize) -> PercolationResult {
+        let side = grid_side(n_nodes);
+        let open = bernoulli(p);
+        let runs: Vec<(usize, bool)> = (0..repetitions)
+            .map(|_| {
+                let present: Vec<bool> = (0..n_nodes).map(|_| open.sample(rng)).collect();
+                let mut components = UnionFind::new(n_nodes);
+                for (a, b) in edges {
+                    if present[*a] && present[*b] {
+                        components.union(*a, *b);
+                    }
+                }
+                percolation_run(&mut components, &present, side)
+            })
+            .collect();
+        percolation_result(&runs, n_nodes)
+    }
+}
+
+/// Simple paths from `node` to `target` as bit masks of edge indices, by depth first search.
+fn simple_paths(adjacency: &[Vec<(usize, usize)>], node: usize, target: usize, visited: &mut Vec<bool>, path: u64, paths: &mut Vec<u64>) {
+    if node == target {
+        paths.push(path);
+        return;
+    }
+    visited[node] = true;
+    for (next, edge) in &adjacency[node] {
+        if !visited[*next] {
+            simple_paths(adjacency, *next, target, visited, path | 1 << edge, paths);
+        }
+    }
+    visited[node] = false;
+}
+
+/// Network reliability with independent edge failures.
+impl DiscreteFiniteRandomExperiment<bool> {
+    /// Proportion of `repetitions` random subgraphs, each edge kept with probability `p`, that are connected.
+    pub fn simulate_network_reliability<R: Rng>(rng: &mut R, n_nodes: usize, edges: &[(usize, usize)], p: f64, repetitions: usize) -> f64 {
+        let open = bernoulli(p);
+        let connected = (0..repetitions)
+            .filter(|_| {
+                let mut components = UnionFind::new(n_nodes);
+                for (a, b) in edges {
+                    if open.sample(rng) {
+                        components.union(*a, *b);
+                    }
+                }
+                components.component_size(0) == n_nodes
+            })
+            .count();
+        connected as f64 / repetitions as f64
+    }
+
+    /// Probability that `s` and `t` are connected, each edge being kept with probability `p`,
+    /// by inclusion-exclusion over the simple paths from `s` to `t`.
+    /// The cost is exponential in the number of paths: for small graphs (at most 64 edges).
+    pub fn theoretical_two_terminal_reliability(n_nodes: usize, edges: &[(usize, usize)], s: usize, t: usize, p: f64) -> f64 {
+        assert!(edges.len() <= 64, "at most 64 edges are supported");
+        if s == t {
+            return 1.0;
+        }
+        let mut adjacency = vec![Vec::new(); n_nodes];
+        for (i, (a, b)) in edges.iter().enumerate() {
+            adjacency[*a].push((*b, i));
+            adjacency[*b].push((*a, i));
+        }
+        let mut paths = Vec::new();
+        simple_paths(&adjacency, s, t, &mut vec![false; n_nodes], 0, &mut paths);
+
+        // P(some path is open) = sum over non empty sets of paths of (-1)^(|set| + 1) p^(edges of their union)
+        let mut reliability = 0.0;
+        let mut stack: Vec<(usize, u64, usize)> = vec![(0, 0, 0)];
+        while let Some((next, union, size)) = stack.pop() {
+            for (i, path) in paths.iter().enumerate().skip(next) {
+                let union = union | path;
+                let sign = if size % 2 == 0 { 1.0 } else { -1.0 };
+                reliability += sign * p.powi(union.count_ones() as i32);
+                stack.push((i + 1, union, size + 1));
+            }
+        }
+        reliability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn percolation_check() {
+        let mut rng = StdRng::seed_from_u64(134);
+        type Exp = DiscreteFiniteRandomExperiment<bool>;
+        let edges = grid_edges(100, 100);
+        assert_eq!(edges.len(), 2 * 100 * 99);
+
+        // bond percolation threshold on the square lattice is 1/2
+        let below = Exp::simulate_bond_percolation(&mut rng, 10_000, &edges, 0.4, 5);
+        let above = Exp::simulate_bond_percolation(&mut rng, 10_000, &edges, 0.6, 5);
+        assert!(below.mean_largest_component_fraction < 0.05);
+        assert_eq!(below.probability_spanning_component, 0.0);
+        assert!(above.mean_largest_component_fraction > 0.5);
+        assert_eq!(above.probability_spanning_component, 1.0);
+        // at the threshold a square is crossed with probability about 1/2, by self-duality
+        let critical = Exp::simulate_bond_percolation(&mut rng, 10_000, &edges, 0.5, 200);
+        assert!((critical.probability_spanning_component - 0.5).abs() < 0.12);
+
+        // site percolation threshold is ~0.593
+        let below = Exp::simulate_site_percolation(&mut rng, 10_000, &edges, 0.5, 5);
+        let above = Exp::simulate_site_percolation(&mut rng, 10_000, &edges, 0.7, 5);
+        assert!(below.mean_largest_component_fraction < 0.1);
+        assert_eq!(below.probability_spanning_component, 0.0);
+        assert!(above.mean_largest_component_fraction > 0.5);
+        assert_eq!(above.probability_spanning_component, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the nodes must form a square grid")]
+    fn percolation_not_a_grid() {
+        let mut rng = StdRng::seed_from_u64(134);
+        DiscreteFiniteRandomExperiment::simulate_bond_percolation(&mut rng, 3, &[(0, 1), (1, 2)], 0.5, 1);
+    }
+
+    #[test]
+    fn network_reliability_check() {
+        let mut rng = StdRng::seed_from_u64(165);
+        type Exp = DiscreteFiniteRandomExperiment<bool>;
+        let path = [(0, 1), (1, 2), (2, 3)];
+        let reliability = Exp::simulate_network_reliability(&mut rng, 4, &path, 0.8, 50_000);
+        assert!((reliability - 0.512).abs() < 0.01);
+        assert!((Exp::theoretical_two_terminal_reliability(4, &path, 0, 3, 0.8) - 0.512).abs() < 1e-12);
+        assert!((Exp::theoretical_two_terminal_reliability(4, &path, 1, 2, 0.8) - 0.8).abs() < 1e-12);
+
+        // bridge network between 0 and 3: 2p^2 + 2p^3 - 5p^4 + 2p^5
+        let bridge = [(0, 1), (0, 2), (1, 2), (1, 3), (2, 3)];
+        let p: f64 = 0.8;
+        let expected = 2.0 * p.powi(2) + 2.0 * p.powi(3) - 5.0 * p.powi(4) + 2.0 * p.powi(5);
+        assert!((Exp::theoretical_two_terminal_reliability(4, &bridge, 0, 3, p) - expected).abs() < 1e-12);
+    }
+}
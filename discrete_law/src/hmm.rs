@@ -0,0 +1,303 @@
+//! Hidden Markov models with experiments as emissions.
+
+use std::marker::PhantomData;
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::{DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment};
+use crate::markov::MarkovChain;
+
+/// Hidden Markov model: a Markov chain over hidden state indices,
+/// each hidden state emitting an observation drawn from its experiment.
+#[derive(Debug)]
+pub struct HiddenMarkovModel<O> {
+    pub transitions: MarkovChain<usize>,
+    pub emissions: Vec<DiscreteFiniteRandomExperiment<O>>,
+    pub initial: DiscreteFiniteRandomExperiment<usize>,
+}
+
+impl<O: PartialEq> HiddenMarkovModel<O> {
+    /// Create the model from its transition matrix, emission experiments and initial law.
+    pub fn new(transition_matrix: &[Vec<f64>], emissions: Vec<DiscreteFiniteRandomExperiment<O>>, initial_law: &[f64]) -> Self {
+        let n = transition_matrix.len();
+        assert_eq!(emissions.len(), n, "one emission experiment per hidden state is required");
+        assert_eq!(initial_law.len(), n, "initial law must have one weight per hidden state");
+        HiddenMarkovModel {
+            transitions: MarkovChain::new((0..n).collect(), transition_matrix),
+            emissions,
+            initial: DiscreteFiniteRandomExperiment::new((0..n).collect(), initial_law),
+        }
+    }
+
+    /// Number of hidden states.
+    pub fn n_states(&self) -> usize {
+        self.emissions.len()
+    }
+
+    /// Probability that hidden state `state` emits `observation`.
+    pub fn emission_probability(&self, state: usize, observation: &O) -> f64 {
+        let emission = &self.emissions[state];
+        emission.omega.iter()
+            .zip(emission.distribution.probabilities())
+            .filter(|(o, _)| *o == observation)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    /// Forward probabilities `alpha[t][i] = P(O_1..O_t, state_t = i)`.
+    pub fn forward_algorithm(&self, observations: &[O]) -> Vec<Vec<f64>> {
+        let a = self.transitions.transition_matrix();
+        let pi = self.initial.distribution.probabilities();
+        let n = self.n_states();
+        let mut alpha: Vec<Vec<f64>> = Vec::with_capacity(observations.len());
+        for (t, o) in observations.iter().enumerate() {
+            let row = (0..n)
+                .map(|j| {
+                    let prior = if t == 0 {
+                        pi[j]
+                    } else {
+                        (0..n).map(|i| alpha[t - 1][i] * a[i][j]).sum()
+                    };
+                    prior * self.emission_probability(j, o)
+                })
+                .collect();
+            alpha.push(row);
+        }
+        alpha
+    }
+
+    /// Most likely hidden state path given `observations` and its log-probability,
+    /// by the Viterbi algorithm in log space.
+    pub fn viterbi(&self, observations: &[O]) -> (Vec<usize>, f64) {
+        if observations.is_empty() {
+            return (Vec::new(), 0.0);
+        }
+        let ln_a: Vec<Vec<f64>> = self.transitions.transition_matrix()
+            .iter()
+            .map(|row| row.iter().map(|p| p.ln()).collect())
+            .collect();
+        let ln_pi: Vec<f64> = self.initial.distribution.probabilities().iter().map(|p| p.ln()).collect();
+        let n = self.n_states();
+
+        let mut delta: Vec<f64> = (0..n)
+            .map(|i| ln_pi[i] + self.emission_probability(i, &observations[0]).ln())
+            .collect();
+        let mut backpointers: Vec<Vec<usize>> = Vec::with_capacity(observations.len());
+        for o in &observations[1..] {
+            let (next, back): (Vec<f64>, Vec<usize>) = (0..n)
+                .map(|j| {
+                    let (best, score) = (0..n)
+                        .map(|i| (i, delta[i] + ln_a[i][j]))
+                        .fold((0, f64::NEG_INFINITY), |b, c| if c.1 > b.1 { c } else { b });
+                    (score + self.emission_probability(j, o).ln(), best)
+                })
+                .unzip();
+            delta = next;
+            backpointers.push(back);
+        }
+
+        let (mut state, log_probability) = delta.iter()
+            .cloned()
+            .enumerate()
+            .fold((0, f64::NEG_INFINITY), |b, c| if c.1 > b.1 { c } else { b });
+        let mut path = vec![state];
+        for back in backpointers.iter().rev() {
+            state = back[state];
+            path.push(state);
+        }
+        path.reverse();
+        (path, log_probability)
+    }
+
+    /// Likelihood `P(O_1..O_T)`, sum of the last forward probabilities.
+    pub fn likelihood(&self, observations: &[O]) -> f64 {
+        self.forward_algorithm(observations).last().map_or(1.0, |row| row.iter().sum())
+    }
+}
+
+impl<O: Clone> HiddenMarkovModel<O> {
+    /// Simulate `n` steps, returning the hidden states and the observations.
+    pub fn simulate<R: Rng>(&self, rng: &mut R, n: usize) -> (Vec<usize>, Vec<O>) {
+        let mut states = Vec::with_capacity(n);
+        let mut observations = Vec::with_capacity(n);
+        for t in 0..n {
+            let state = if t == 0 {
+                self.initial.sample(rng)
+            } else {
+                self.transitions.step(rng, states[t - 1])
+            };
+            states.push(state);
+            observations.push(self.emissions[state].sample(rng));
+        }
+        (states, observations)
+    }
+}
+
+
+/// Bootstrap particle filter over the states of a Markov chain, observations of type `O`.
+#[derive(Debug)]
+pub struct ParticleFilter<S, O> {
+    /// Index of each particle in the chain states.
+    indices: Vec<usize>,
+    particles: Vec<S>,
+    /// Normalized weights.
+    weights: Vec<f64>,
+    observation: PhantomData<O>,
+}
+
+impl<S: Clone, O> ParticleFilter<S, O> {
+    /// `n_particles` particles drawn from `initial_law` over the states of `transition`.
+    pub fn new<R: Rng>(rng: &mut R, transition: &MarkovChain<S>, initial_law: &[f64], n_particles: usize) -> Self {
+        let initial = DiscreteFiniteDistribution::new(initial_law);
+        let indices: Vec<usize> = (0..n_particles).map(|_| initial.sample(rng)).collect();
+        ParticleFilter {
+            particles: indices.iter().map(|i| transition.states[*i].clone()).collect(),
+            indices,
+            weights: vec![1.0 / n_particles as f64; n_particles],
+            observation: PhantomData,
+        }
+    }
+
+    /// Move every particle one step along `transition`.
+    pub fn predict<R: Rng>(&mut self, rng: &mut R, transition: &MarkovChain<S>) {
+        for (index, particle) in self.indices.iter_mut().zip(&mut self.particles) {
+            *index = transition.step(rng, *index);
+            *particle = transition.states[*index].clone();
+        }
+    }
+
+    /// Reweight the particles by the likelihood of `observation`.
+    /// The weights are left unchanged when every particle has likelihood 0.
+    pub fn update(&mut self, observation: &O, likelihood: &dyn Fn(&S, &O) -> f64) {
+        let weights: Vec<f64> = self.particles.iter()
+            .zip(&self.weights)
+            .map(|(s, w)| w * likelihood(s, observation))
+            .collect();
+        if weights.iter().any(|w| *w > 0.0) {
+            self.weights = DiscreteFiniteDistribution::normalize(&weights);
+        }
+    }
+
+    /// Multinomial resampling, the weights become uniform.
+    pub fn resample<R: Rng>(&mut self, rng: &mut R) {
+        let law = DiscreteFiniteDistribution::new(&self.weights);
+        let n = self.weights.len();
+        let picks: Vec<usize> = (0..n).map(|_| law.sample(rng)).collect();
+        self.indices = picks.iter().map(|k| self.indices[*k]).collect();
+        self.particles = picks.iter().map(|k| self.particles[*k].clone()).collect();
+        self.weights = vec![1.0 / n as f64; n];
+    }
+
+    /// Particles with their normalized weights.
+    pub fn estimate(&self) -> Vec<(S, f64)> {
+        self.particles.iter().cloned().zip(self.weights.iter().copied()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn weather() -> HiddenMarkovModel<char> {
+        // hidden: 0 rainy, 1 sunny; observed: walk, shop, clean
+        HiddenMarkovModel::new(
+            &[vec![0.7, 0.3], vec![0.4, 0.6]],
+            vec![
+                DiscreteFiniteRandomExperiment::new(vec!['w', 's', 'c'], &[0.1, 0.4, 0.5]),
+                DiscreteFiniteRandomExperiment::new(vec!['w', 's', 'c'], &[0.6, 0.3, 0.1]),
+            ],
+            &[0.6, 0.4],
+        )
+    }
+
+    #[test]
+    fn forward_check() {
+        let mut rng = StdRng::seed_from_u64(131);
+        let hmm = weather();
+        let obs = ['w', 's', 'c'];
+
+        // brute force over the 8 hidden paths
+        let a = [[0.7, 0.3], [0.4, 0.6]];
+        let pi = [0.6, 0.4];
+        let mut brute = 0.0;
+        for path in 0..8usize {
+            let s: Vec<usize> = (0..3).map(|t| (path >> t) & 1).collect();
+            let mut p = pi[s[0]] * hmm.emission_probability(s[0], &obs[0]);
+            for t in 1..3 {
+                p *= a[s[t - 1]][s[t]] * hmm.emission_probability(s[t], &obs[t]);
+            }
+            brute += p;
+        }
+        assert!((hmm.likelihood(&obs) - brute).abs() < 1e-12);
+        assert!((hmm.forward_algorithm(&obs)[0][0] - 0.06).abs() < 1e-12);
+
+        let (states, observations) = hmm.simulate(&mut rng, 100_000);
+        assert_eq!(states.len(), observations.len());
+        let sunny_walks = states.iter().zip(&observations).filter(|(s, o)| **s == 1 && **o == 'w').count();
+        let sunny = states.iter().filter(|s| **s == 1).count();
+        assert!((sunny_walks as f64 / sunny as f64 - 0.6).abs() < 0.01);
+    }
+
+    #[test]
+    fn viterbi_check() {
+        let hmm = weather();
+        let a = [[0.7, 0.3], [0.4, 0.6]];
+        let pi = [0.6, 0.4];
+
+        for obs in [vec!['w', 's', 'c'], vec!['c', 'c', 'w', 'w'], vec!['s']] {
+            let (path, log_p) = hmm.viterbi(&obs);
+            let mut best = (Vec::new(), 0.0);
+            for code in 0..(1usize << obs.len()) {
+                let s: Vec<usize> = (0..obs.len()).map(|t| (code >> t) & 1).collect();
+                let mut p = pi[s[0]] * hmm.emission_probability(s[0], &obs[0]);
+                for t in 1..obs.len() {
+                    p *= a[s[t - 1]][s[t]] * hmm.emission_probability(s[t], &obs[t]);
+                }
+                if p > best.1 {
+                    best = (s, p);
+                }
+            }
+            assert_eq!(path, best.0);
+            assert!((log_p - best.1.ln()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn particle_filter_check() {
+        let mut rng = StdRng::seed_from_u64(144);
+        let hmm = HiddenMarkovModel::new(
+            &[vec![0.8, 0.1, 0.1], vec![0.2, 0.6, 0.2], vec![0.1, 0.3, 0.6]],
+            vec![
+                DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.9, 0.1]),
+                DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5]),
+                DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.2, 0.8]),
+            ],
+            &[1.0, 1.0, 1.0],
+        );
+        let (_, observations) = hmm.simulate(&mut rng, 100);
+        let likelihood = |s: &usize, o: &i32| hmm.emission_probability(*s, o);
+
+        let mut filter = ParticleFilter::new(&mut rng, &hmm.transitions, &[1.0, 1.0, 1.0], 5_000);
+        for (t, o) in observations.iter().enumerate() {
+            if t > 0 {
+                filter.resample(&mut rng);
+                filter.predict(&mut rng, &hmm.transitions);
+            }
+            filter.update(o, &likelihood);
+        }
+
+        let alpha = hmm.forward_algorithm(&observations);
+        let exact = DiscreteFiniteDistribution::normalize(&alpha[99]);
+        let mut estimated = [0.0; 3];
+        for (s, w) in filter.estimate() {
+            estimated[s] += w;
+        }
+        assert!((estimated.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        for s in 0..3 {
+            assert!((estimated[s] - exact[s]).abs() < 0.03, "{estimated:?} {exact:?}");
+        }
+    }
+}
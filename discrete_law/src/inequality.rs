@@ -0,0 +1,74 @@
+//! Inequality measures of a law of non negative values.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Lorenz curve and Gini coefficient, the outcomes of `self` being non negative values (incomes, wealth...).
+impl<T: Into<f64> + Clone + Ord> DiscreteFiniteRandomExperiment<T> {
+    /// Points `(population share, value share)` of the Lorenz curve, from `(0, 0)` to `(1, 1)`,
+    /// the population being sorted by increasing value.
+    pub fn lorenz_curve(&self) -> Vec<(f64, f64)> {
+        let mut outcomes: Vec<(T, f64)> = self.omega.iter().cloned().zip(self.distribution.probabilities()).collect();
+        outcomes.sort_by(|a, b| a.0.cmp(&b.0));
+        let mean = self.expected_value();
+        let mut points = vec![(0.0, 0.0)];
+        let (mut population, mut value) = (0.0, 0.0);
+        for (x, p) in outcomes {
+            population += p;
+            value += p * x.into() / mean;
+            points.push((population, value));
+        }
+        points
+    }
+
+    /// Gini coefficient, twice the area between the diagonal and the Lorenz curve.
+    pub fn gini_coefficient(&self) -> f64 {
+        let area_under: f64 = self.lorenz_curve()
+            .windows(2)
+            .map(|w| 0.5 * (w[1].0 - w[0].0) * (w[1].1 + w[0].1))
+            .sum();
+        1.0 - 2.0 * area_under
+    }
+
+    /// Gini coefficient of `n` draws, `2 sum_i i x_(i) / (n sum x) - (n + 1) / n` over the sorted draws.
+    pub fn simulate_gini<R: Rng>(&self, rng: &mut R, n: usize) -> f64 {
+        let mut draws: Vec<T> = (0..n).map(|_| self.sample(rng)).collect();
+        draws.sort();
+        let values: Vec<f64> = draws.into_iter().map(Into::into).collect();
+        let total: f64 = values.iter().sum();
+        let weighted: f64 = values.iter().enumerate().map(|(i, x)| (i + 1) as f64 * x).sum();
+        let n = n as f64;
+        2.0 * weighted / (n * total) - (n + 1.0) / n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn gini_check() {
+        let mut rng = StdRng::seed_from_u64(164);
+        let degenerate = DiscreteFiniteRandomExperiment::new(vec![5u32, 7], &[1.0, 0.0]);
+        assert!(degenerate.gini_coefficient().abs() < 1e-12);
+        assert!(degenerate.simulate_gini(&mut rng, 100).abs() < 1e-12);
+
+        let unequal = DiscreteFiniteRandomExperiment::new(vec![1u32, 1000], &[0.99, 0.01]);
+        let curve = unequal.lorenz_curve();
+        assert_eq!(curve.len(), 3);
+        assert!((curve[2].0 - 1.0).abs() < 1e-12 && (curve[2].1 - 1.0).abs() < 1e-12);
+        let gini = unequal.gini_coefficient();
+        // sum p_i p_j |x_i - x_j| / (2 mean)
+        assert!((gini - 0.99 * 0.01 * 999.0 / 10.99).abs() < 1e-9);
+        assert!(gini > 0.85);
+        assert!((unequal.simulate_gini(&mut rng, 100_000) - gini).abs() < 0.01);
+
+        // uniform on 1..=4: mean absolute difference 1.25, Gini 1.25 / (2 * 2.5)
+        let uniform = DiscreteFiniteRandomExperiment::new(vec![1u32, 2, 3, 4], &[1.0; 4]);
+        assert!((uniform.gini_coefficient() - 0.25).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,465 @@
+//! Information theory quantities (in nats).
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use ordered_float::OrderedFloat;
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::large_deviations::cumulants;
+use crate::statistics::softmax;
+use crate::{DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment, ProjectionError, StatError};
+
+/// `p * ln(q)` with the convention `0 * ln(0) = 0`.
+fn p_ln_q(p: f64, q: f64) -> f64 {
+    if p == 0.0 { 0.0 } else { p * q.ln() }
+}
+
+impl DiscreteFiniteDistribution {
+    /// Shannon entropy `-sum p_i ln p_i`.
+    pub fn entropy(&self) -> f64 {
+        -self.probabilities().iter().map(|p| p_ln_q(*p, *p)).sum::<f64>()
+    }
+
+    /// Cross entropy `-sum p_i ln q_i` where `self` is p and `other` is q.
+    pub fn cross_entropy(&self, other: &DiscreteFiniteDistribution) -> Result<f64, StatError> {
+        if self.len() != other.len() {
+            return Err(StatError::SupportMismatch(self.len(), other.len()));
+        }
+        Ok(-self.probabilities().iter()
+            .zip(other.probabilities())
+            .map(|(p, q)| p_ln_q(*p, q))
+            .sum::<f64>())
+    }
+}
+
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Shannon entropy of the law.
+    pub fn entropy(&self) -> f64 {
+        self.distribution.entropy()
+    }
+
+    /// Cross entropy loss of the `predicted` law when `self` is the true law.
+    pub fn cross_entropy_loss(&self, predicted: &DiscreteFiniteDistribution) -> Result<f64, StatError> {
+        self.distribution.cross_entropy(predicted)
+    }
+}
+
+/// Cross entropy of a Bernoulli prediction `y_pred` for the label probability `y_true`.
+pub fn binary_cross_entropy(y_true: f64, y_pred: f64) -> f64 {
+    -(p_ln_q(y_true, y_pred) + p_ln_q(1.0 - y_true, 1.0 - y_pred))
+}
+
+
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Information projection of the law on the laws of mean `target_mean`: the law `P` minimizing `KL(P || Q)`
+    /// subject to `E_P[X] = target_mean`, where `Q` is the law of `self`.
+    ///
+    /// It is the exponential tilting `p_i = q_i exp(theta x_i) / E_Q[exp(theta X)]`, `theta` being found by bisection
+    /// on the increasing tilted mean. At an end of the support the tilting degenerates into `Q` conditioned on that outcome.
+    pub fn information_projection(&self, target_mean: f64) -> Result<DiscreteFiniteRandomExperiment<f64>, ProjectionError> {
+        let values: Vec<f64> = self.omega.iter().map(|o| o.clone().into()).collect();
+        let q = self.distribution.probabilities();
+        let support = values.iter().zip(&q).filter(|(_, p)| **p > 0.0).map(|(x, _)| *x);
+        let min = support.clone().fold(f64::INFINITY, f64::min);
+        let max = support.fold(f64::NEG_INFINITY, f64::max);
+        if !(min..=max).contains(&target_mean) {
+            return Err(ProjectionError::InfeasibleMean(target_mean));
+        }
+
+        let law: Vec<f64> = if target_mean == min || target_mean == max {
+            values.iter().zip(&q).map(|(x, p)| if *x == target_mean { *p } else { 0.0 }).collect()
+        } else {
+            let (mut lo, mut hi) = (-1.0, 1.0);
+            while cumulants(&values, &q, lo).1 > target_mean {
+                lo *= 2.0;
+            }
+            while cumulants(&values, &q, hi).1 < target_mean {
+                hi *= 2.0;
+            }
+            for _ in 0..200 {
+                let mid = 0.5 * (lo + hi);
+                if cumulants(&values, &q, mid).1 < target_mean {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            let theta = 0.5 * (lo + hi);
+            softmax(&values.iter().zip(&q).map(|(x, p)| theta * x + p.ln()).collect::<Vec<f64>>())
+        };
+        Ok(DiscreteFiniteRandomExperiment::new(values, &DiscreteFiniteDistribution::normalize(&law)))
+    }
+}
+
+
+/// Binary code words of a Huffman code for `probabilities`: the two least probable nodes are merged
+/// until a single tree is left, ties broken by creation order. A single outcome gets the code `0`.
+fn huffman_code_words(probabilities: &[f64]) -> Vec<String> {
+    let mut codes = vec![String::new(); probabilities.len()];
+    if probabilities.len() == 1 {
+        codes[0].push('0');
+        return codes;
+    }
+    // leaves of each node of the tree, the first ones being the outcomes
+    let mut leaves: Vec<Vec<usize>> = (0..probabilities.len()).map(|i| vec![i]).collect();
+    let mut heap: BinaryHeap<Reverse<(OrderedFloat<f64>, usize)>> = probabilities.iter()
+        .enumerate()
+        .map(|(i, p)| Reverse((OrderedFloat(*p), i)))
+        .collect();
+    while let (Some(Reverse((p0, a))), Some(Reverse((p1, b)))) = (heap.pop(), heap.pop()) {
+        // codes are built from the leaves up, so bits are prepended
+        for (bit, node) in [('0', a), ('1', b)] {
+            for leaf in &leaves[node] {
+                codes[*leaf].insert(0, bit);
+            }
+        }
+        let merged = [leaves[a].clone(), leaves[b].clone()].concat();
+        leaves.push(merged);
+        heap.push(Reverse((p0 + p1, leaves.len() - 1)));
+    }
+    codes
+}
+
+/// Optimal prefix free coding of the outcomes.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Huffman code word of each outcome.
+    pub fn huffman_codes(&self) -> Vec<(T, String)> {
+        self.omega.iter().cloned().zip(huffman_code_words(&self.distribution.probabilities())).collect()
+    }
+
+    /// Expected length in bits of the Huffman code, between the entropy in bits `H / ln 2` and `H / ln 2 + 1`.
+    pub fn huffman_expected_length(&self) -> f64 {
+        let probabilities = self.distribution.probabilities();
+        huffman_code_words(&probabilities).iter().zip(&probabilities).map(|(c, p)| p * c.len() as f64).sum()
+    }
+}
+
+
+/// Number of phrases of the Lempel-Ziv (1976) parse of `sequence` (Kaspar and Schuster algorithm):
+/// each phrase is the shortest extension of a substring already seen starting before it.
+fn lz76_phrases(sequence: &[usize]) -> usize {
+    let n = sequence.len();
+    if n <= 1 {
+        return n;
+    }
+    let (mut c, mut l, mut i, mut k, mut k_max) = (1, 1, 0, 1, 1);
+    loop {
+        if sequence[i + k - 1] == sequence[l + k - 1] {
+            k += 1;
+            if l + k > n {
+                c += 1;
+                break;
+            }
+        } else {
+            k_max = k_max.max(k);
+            i += 1;
+            if i == l {
+                // no earlier copy is longer: a new phrase starts
+                c += 1;
+                l += k_max;
+                if l + 1 > n {
+                    break;
+                }
+                i = 0;
+                k_max = 1;
+            }
+            k = 1;
+        }
+    }
+    c
+}
+
+/// Lempel-Ziv complexity.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Number of phrases of the LZ76 parse of `n` draws (as outcome indices).
+    pub fn lempel_ziv_complexity<R: Rng>(&self, rng: &mut R, n: usize) -> f64 {
+        let sequence: Vec<usize> = (0..n).map(|_| self.distribution.sample(rng)).collect();
+        lz76_phrases(&sequence) as f64
+    }
+
+    /// Asymptotic number of phrases `n H / log2(n)` of an i.i.d. sequence, `H` being the entropy in bits.
+    pub fn theoretical_lz_complexity(&self, n: usize) -> f64 {
+        let bits = self.entropy() / std::f64::consts::LN_2;
+        n as f64 * bits / (n as f64).log2()
+    }
+}
+
+
+/// Number of bits of the arithmetic coder registers.
+const CODER_BITS: u32 = 32;
+const CODER_HALF: u64 = 1 << (CODER_BITS - 1);
+const CODER_QUARTER: u64 = 1 << (CODER_BITS - 2);
+/// Total of the integer frequencies approximating the law, small enough to never empty the coder interval.
+const CODER_FREQUENCY_TOTAL: u64 = 1 << 16;
+
+/// Cumulative integer frequencies `0 = c_0 <= ... <= c_n = CODER_FREQUENCY_TOTAL` approximating the law,
+/// each outcome of positive probability keeping a frequency of at least 1.
+fn coder_cumulative_frequencies(probabilities: &[f64]) -> Vec<u64> {
+    let mut frequencies: Vec<u64> = probabilities.iter()
+        .map(|p| if *p > 0.0 { ((p * CODER_FREQUENCY_TOTAL as f64) as u64).max(1) } else { 0 })
+        .collect();
+    assert!(
+        frequencies.iter().filter(|f| **f > 0).count() as u64 <= CODER_FREQUENCY_TOTAL,
+        "the arithmetic coder handles at most {CODER_FREQUENCY_TOTAL} outcomes of positive probability"
+    );
+    // the rounding error goes to the most probable outcomes, none dropping below 1
+    let mut total: u64 = frequencies.iter().sum();
+    while total != CODER_FREQUENCY_TOTAL {
+        let largest = (0..frequencies.len()).max_by_key(|i| frequencies[*i]).unwrap();
+        if total < CODER_FREQUENCY_TOTAL {
+            frequencies[largest] += CODER_FREQUENCY_TOTAL - total;
+            total = CODER_FREQUENCY_TOTAL;
+        } else {
+            let cut = (total - CODER_FREQUENCY_TOTAL).min(frequencies[largest] - 1);
+            frequencies[largest] -= cut;
+            total -= cut;
+        }
+    }
+    std::iter::once(0)
+        .chain(frequencies.iter().scan(0, |acc, f| {
+            *acc += f;
+            Some(*acc)
+        }))
+        .collect()
+}
+
+/// Append `bit` to the bit stream `bytes` of `length` bits, most significant bit first.
+fn push_bit(bytes: &mut Vec<u8>, length: &mut usize, bit: bool) {
+    if length.is_multiple_of(8) {
+        bytes.push(0);
+    }
+    if bit {
+        bytes[*length / 8] |= 0x80 >> (*length % 8);
+    }
+    *length += 1;
+}
+
+/// Bit `i` of the stream `bytes`, zero past its end.
+fn read_bit(bytes: &[u8], i: usize) -> u64 {
+    bytes.get(i / 8).map_or(0, |b| ((b >> (7 - i % 8)) & 1) as u64)
+}
+
+/// Arithmetic coding of sequences of outcome indices.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Arithmetic code of `symbols`, the cumulative distribution (approximated by integer frequencies)
+    /// giving the subinterval of each outcome. The length is close to `sum -log2 p(symbol)` bits.
+    pub fn arithmetic_encode(&self, symbols: &[usize]) -> Vec<u8> {
+        let cumulative = coder_cumulative_frequencies(&self.distribution.probabilities());
+        let (mut bytes, mut length) = (Vec::new(), 0);
+        let (mut low, mut high, mut pending) = (0u64, (1u64 << CODER_BITS) - 1, 0);
+        let mut emit = |bit: bool, pending: &mut usize, bytes: &mut Vec<u8>| {
+            push_bit(bytes, &mut length, bit);
+            for _ in 0..*pending {
+                push_bit(bytes, &mut length, !bit);
+            }
+            *pending = 0;
+        };
+        for s in symbols {
+            assert!(cumulative[*s + 1] > cumulative[*s], "outcome {s} has zero probability and can't be encoded");
+            let range = high - low + 1;
+            high = low + range * cumulative[*s + 1] / CODER_FREQUENCY_TOTAL - 1;
+            low += range * cumulative[*s] / CODER_FREQUENCY_TOTAL;
+            loop {
+                if high < CODER_HALF {
+                    emit(false, &mut pending, &mut bytes);
+                } else if low >= CODER_HALF {
+                    emit(true, &mut pending, &mut bytes);
+                    low -= CODER_HALF;
+                    high -= CODER_HALF;
+                } else if low >= CODER_QUARTER && high < 3 * CODER_QUARTER {
+                    // the interval straddles the middle: the next bit is decided later
+                    pending += 1;
+                    low -= CODER_QUARTER;
+                    high -= CODER_QUARTER;
+                } else {
+                    break;
+                }
+                low *= 2;
+                high = 2 * high + 1;
+            }
+        }
+        // two more bits select a point inside the final interval
+        pending += 1;
+        emit(low >= CODER_QUARTER, &mut pending, &mut bytes);
+        bytes
+    }
+
+    /// The `n_symbols` outcome indices encoded in `bytes` by `arithmetic_encode`.
+    pub fn arithmetic_decode(&self, bytes: &[u8], n_symbols: usize) -> Vec<usize> {
+        let cumulative = coder_cumulative_frequencies(&self.distribution.probabilities());
+        let (mut low, mut high) = (0u64, (1u64 << CODER_BITS) - 1);
+        let mut value = (0..CODER_BITS as usize).fold(0, |v, i| 2 * v + read_bit(bytes, i));
+        let mut position = CODER_BITS as usize;
+        let mut symbols = Vec::with_capacity(n_symbols);
+        for _ in 0..n_symbols {
+            let range = high - low + 1;
+            let scaled = ((value - low + 1) * CODER_FREQUENCY_TOTAL - 1) / range;
+            let s = (0..cumulative.len() - 1)
+                .find(|i| cumulative[*i] <= scaled && scaled < cumulative[i + 1])
+                .unwrap();
+            symbols.push(s);
+            high = low + range * cumulative[s + 1] / CODER_FREQUENCY_TOTAL - 1;
+            low += range * cumulative[s] / CODER_FREQUENCY_TOTAL;
+            loop {
+                if high < CODER_HALF {
+                    // lower half, nothing to shift out
+                } else if low >= CODER_HALF {
+                    value -= CODER_HALF;
+                    low -= CODER_HALF;
+                    high -= CODER_HALF;
+                } else if low >= CODER_QUARTER && high < 3 * CODER_QUARTER {
+                    value -= CODER_QUARTER;
+                    low -= CODER_QUARTER;
+                    high -= CODER_QUARTER;
+                } else {
+                    break;
+                }
+                low *= 2;
+                high = 2 * high + 1;
+                value = 2 * value + read_bit(bytes, position);
+                position += 1;
+            }
+        }
+        symbols
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn cross_entropy_check() {
+        let mut rng = StdRng::seed_from_u64(115);
+        let truth = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3], &[0.2, 0.3, 0.5]);
+        let h = truth.entropy();
+        assert!((truth.cross_entropy_loss(&truth.distribution).unwrap() - h).abs() < 1e-12);
+
+        for _ in 0..100 {
+            let q: Vec<f64> = (0..3).map(|_| rng.random::<f64>()).collect();
+            let loss = truth.cross_entropy_loss(&DiscreteFiniteDistribution::new(&q)).unwrap();
+            assert!(loss >= h - 1e-12);
+        }
+
+        let other = DiscreteFiniteDistribution::new(&[0.5, 0.5]);
+        assert_eq!(truth.cross_entropy_loss(&other), Err(StatError::SupportMismatch(3, 2)));
+
+        assert!((binary_cross_entropy(1.0, 0.5) - 2f64.ln()).abs() < 1e-12);
+        assert_eq!(binary_cross_entropy(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn information_projection_check() {
+        let die = DiscreteFiniteRandomExperiment::new(vec![1u8, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let projection = die.information_projection(4.0).unwrap();
+        assert!((projection.expected_value() - 4.0).abs() < 1e-6);
+        let p = projection.distribution.probabilities();
+        assert!(p.windows(2).all(|w| w[0] < w[1]));
+
+        // another law of mean 4 is farther from the fair die
+        let kl = |law: &[f64]| law.iter().map(|p| p_ln_q(*p, p * 6.0)).sum::<f64>();
+        let mut other = p.clone();
+        other[0] += 0.01;
+        other[1] -= 0.02;
+        other[2] += 0.01;
+        assert!(kl(&p) < kl(&other));
+
+        let unchanged = die.information_projection(3.5).unwrap().distribution.probabilities();
+        assert!(unchanged.iter().all(|p| (p - 1.0 / 6.0).abs() < 1e-12));
+        assert_eq!(die.information_projection(6.0).unwrap().distribution.probabilities(), vec![0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(die.information_projection(6.5).unwrap_err(), ProjectionError::InfeasibleMean(6.5));
+    }
+
+    #[test]
+    fn huffman_check() {
+        let coin = DiscreteFiniteRandomExperiment::new(vec!['h', 't'], &[0.5, 0.5]);
+        assert_eq!(coin.huffman_expected_length(), 1.0);
+
+        let uniform = DiscreteFiniteRandomExperiment::new((0..8).collect(), &[1.0; 8]);
+        let bits = uniform.entropy() / 2f64.ln();
+        assert!((uniform.huffman_expected_length() - bits).abs() < 1e-12);
+        assert!(uniform.huffman_codes().iter().all(|(_, c)| c.len() == 3));
+
+        let exp = DiscreteFiniteRandomExperiment::new(vec!['a', 'b', 'c', 'd'], &[0.4, 0.3, 0.2, 0.1]);
+        let codes = exp.huffman_codes();
+        let lengths: Vec<usize> = codes.iter().map(|(_, c)| c.len()).collect();
+        assert_eq!(lengths, vec![1, 2, 3, 3]);
+        // prefix free
+        for (i, (_, a)) in codes.iter().enumerate() {
+            assert!(codes.iter().enumerate().all(|(j, (_, b))| i == j || !b.starts_with(a.as_str())));
+        }
+        let bits = exp.entropy() / 2f64.ln();
+        let length = exp.huffman_expected_length();
+        assert!((length - 1.9).abs() < 1e-12);
+        assert!(bits <= length && length < bits + 1.0);
+
+        let single = DiscreteFiniteRandomExperiment::new(vec!['a'], &[1.0]);
+        assert_eq!(single.huffman_codes(), vec![('a', "0".to_string())]);
+    }
+
+    #[test]
+    fn lempel_ziv_check() {
+        let mut rng = StdRng::seed_from_u64(179);
+        // 0 | 001 | 10 | 100 | 1000 | 101
+        assert_eq!(lz76_phrases(&[0, 0, 0, 1, 1, 0, 1, 0, 0, 1, 0, 0, 0, 1, 0, 1]), 6);
+        assert_eq!(lz76_phrases(&[]), 0);
+        assert_eq!(lz76_phrases(&[1]), 1);
+
+        // the first draw, then a copy of it for the rest of the sequence
+        let constant = DiscreteFiniteRandomExperiment::new(vec!['a', 'b'], &[1.0, 0.0]);
+        assert_eq!(constant.lempel_ziv_complexity(&mut rng, 1_000), 2.0);
+
+        let coin = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5]);
+        let ratio = |rng: &mut StdRng, n: usize| coin.lempel_ziv_complexity(rng, n) / coin.theoretical_lz_complexity(n);
+        let small = ratio(&mut rng, 200);
+        let large = ratio(&mut rng, 20_000);
+        assert!((large - 1.0).abs() < (small - 1.0).abs());
+        assert!((large - 1.0).abs() < 0.15);
+    }
+
+    #[test]
+    fn arithmetic_coding_check() {
+        let mut rng = StdRng::seed_from_u64(180);
+        for law in [vec![0.5, 0.25, 0.125, 0.125], vec![0.9, 0.05, 0.03, 0.02], vec![0.2, 0.0, 0.3, 0.5]] {
+            let exp = DiscreteFiniteRandomExperiment::new(vec!['a', 'b', 'c', 'd'], &law);
+            let symbols: Vec<usize> = (0..1_000).map(|_| exp.distribution.sample(&mut rng)).collect();
+            let bytes = exp.arithmetic_encode(&symbols);
+            assert_eq!(exp.arithmetic_decode(&bytes, symbols.len()), symbols);
+
+            let bits_per_symbol = 8.0 * bytes.len() as f64 / symbols.len() as f64;
+            let entropy = exp.entropy() / std::f64::consts::LN_2;
+            assert!((bits_per_symbol - entropy).abs() < 1.0);
+        }
+        let coin = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5]);
+        assert!(coin.arithmetic_decode(&coin.arithmetic_encode(&[]), 0).is_empty());
+    }
+
+    #[test]
+    fn arithmetic_coding_tiny_probabilities() {
+        let mut rng = StdRng::seed_from_u64(180);
+        // 40000 outcomes below the frequency resolution, rounded up at the expense of the two large ones
+        let mut law = vec![1e-6; 40_000];
+        law.extend([0.48, 0.48]);
+        let exp = DiscreteFiniteRandomExperiment::new((0..law.len()).collect(), &law);
+        let cumulative = coder_cumulative_frequencies(&exp.distribution.probabilities());
+        assert_eq!(cumulative[cumulative.len() - 1], CODER_FREQUENCY_TOTAL);
+        assert!(cumulative.windows(2).all(|w| w[0] < w[1]));
+
+        let mut symbols: Vec<usize> = (0..1_000).map(|_| exp.distribution.sample(&mut rng)).collect();
+        symbols.extend([0, 17, 39_999, 40_001]);
+        let bytes = exp.arithmetic_encode(&symbols);
+        assert_eq!(exp.arithmetic_decode(&bytes, symbols.len()), symbols);
+    }
+
+    #[test]
+    #[should_panic(expected = "the arithmetic coder handles at most 65536 outcomes of positive probability")]
+    fn arithmetic_coding_too_many_outcomes() {
+        let exp = DiscreteFiniteRandomExperiment::new((0..70_000).collect(), &[1.0; 70_000]);
+        exp.arithmetic_encode(&[0]);
+    }
+}
@@ -0,0 +1,130 @@
+//! Tail probabilities of sums of i.i.d. draws: simulation and saddlepoint approximation.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::statistics::{log_sum_exp, softmax};
+use crate::{standard_normal_cdf, DiscreteFiniteRandomExperiment};
+
+/// Greatest common divisor.
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// Cumulant generating function `K(theta) = ln E[exp(theta X)]` of a law with its first two derivatives.
+pub(crate) fn cumulants(values: &[f64], probabilities: &[f64], theta: f64) -> (f64, f64, f64) {
+    let exponents: Vec<f64> = values.iter().zip(probabilities).map(|(x, p)| theta * x + p.ln()).collect();
+    let tilted = softmax(&exponents);
+    let mean = values.iter().zip(&tilted).map(|(x, w)| x * w).sum::<f64>();
+    let variance = values.iter().zip(&tilted).map(|(x, w)| w * (x - mean).powi(2)).sum::<f64>();
+    (log_sum_exp(&exponents), mean, variance)
+}
+
+/// Tails of the sum `S_n` of `n` draws.
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Proportion of `repetitions` simulated sums with `S_n > n * x`.
+    pub fn simulate_large_deviations<R: Rng>(&self, rng: &mut R, x: f64, n: usize, repetitions: usize) -> f64 {
+        let threshold = n as f64 * x;
+        let hits = (0..repetitions)
+            .filter(|_| (0..n).map(|_| self.sample(rng).into()).sum::<f64>() > threshold)
+            .count();
+        hits as f64 / repetitions as f64
+    }
+
+    /// Lugannani-Rice saddlepoint approximation of `P(S_n > n * x)`.
+    ///
+    /// When every outcome is an integer the sum lives on a lattice: the event becomes
+    /// `S_n >= s` for the first lattice point `s` above `n * x`, with Daniels' lattice correction.
+    /// Close to the mean the saddlepoint formula is singular and the normal approximation is used.
+    pub fn saddlepoint_tail_probability(&self, x: f64, n: usize) -> f64 {
+        let (values, probabilities): (Vec<f64>, Vec<f64>) = self.omega.iter()
+            .map(|o| o.clone().into())
+            .zip(self.distribution.probabilities())
+            .filter(|(_, p)| *p > 0.0)
+            .unzip();
+        let nf = n as f64;
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+        // lattice span, None for a non lattice law
+        let span = if values.iter().all(|v| v.fract() == 0.0) {
+            let g = values.iter().fold(0, |g, v| gcd(g, *v as i64 - min as i64));
+            Some(g.max(1) as f64)
+        } else {
+            None
+        };
+        let a = match span {
+            Some(h) => (nf * min + (((nf * x - nf * min) / h).floor() + 1.0) * h) / nf,
+            None => x,
+        };
+        if a > max || (span.is_none() && a >= max) {
+            return 0.0;
+        }
+        if a <= min {
+            return 1.0;
+        }
+
+        // solve K'(theta) = a by bisection, K' is increasing
+        let (mut lo, mut hi) = (-1.0, 1.0);
+        while cumulants(&values, &probabilities, lo).1 > a {
+            lo *= 2.0;
+        }
+        while cumulants(&values, &probabilities, hi).1 < a {
+            hi *= 2.0;
+        }
+        for _ in 0..200 {
+            let mid = 0.5 * (lo + hi);
+            if cumulants(&values, &probabilities, mid).1 < a {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let theta = 0.5 * (lo + hi);
+        let (k, _, k2) = cumulants(&values, &probabilities, theta);
+
+        let w = theta.signum() * (2.0 * nf * (theta * a - k)).max(0.0).sqrt();
+        if w.abs() < 1e-6 {
+            return 1.0 - standard_normal_cdf(w);
+        }
+        let u = match span {
+            Some(h) => (1.0 - (-h * theta).exp()) / h * (nf * k2).sqrt(),
+            None => theta * (nf * k2).sqrt(),
+        };
+        let density = (-0.5 * w * w).exp() / (2.0 * std::f64::consts::PI).sqrt();
+        (standard_normal_cdf(-w) + density * (1.0 / u - 1.0 / w)).clamp(0.0, 1.0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn saddlepoint_check() {
+        let mut rng = StdRng::seed_from_u64(146);
+        let die = DiscreteFiniteRandomExperiment::new(vec![1u8, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let x = die.expected_value() + die.variance().sqrt();
+
+        // P(S_4 >= 21) = 35 / 1296
+        let saddlepoint = die.saddlepoint_tail_probability(x, 4);
+        assert!((saddlepoint / (35.0 / 1296.0) - 1.0).abs() < 0.05);
+        let simulated = die.simulate_large_deviations(&mut rng, x, 4, 200_000);
+        assert!((saddlepoint / simulated - 1.0).abs() < 0.1);
+
+        let sums = die.theoretical_sum_distribution(10);
+        let exact: f64 = sums.omega.iter()
+            .zip(sums.distribution.probabilities())
+            .filter(|(s, _)| **s as f64 > 10.0 * x)
+            .map(|(_, p)| p)
+            .sum();
+        assert!((die.saddlepoint_tail_probability(x, 10) / exact - 1.0).abs() < 0.02);
+
+        assert_eq!(die.saddlepoint_tail_probability(6.0, 3), 0.0);
+        assert_eq!(die.saddlepoint_tail_probability(0.5, 3), 1.0);
+        assert!((standard_normal_cdf(1.96) - 0.975).abs() < 1e-4);
+    }
+}
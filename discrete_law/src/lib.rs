@@ -5,23 +5,23 @@
 //!
 //! # Example:
 //! ```
-//! fn main() {
-//!    let omega = ["A", "B", "C"];
-//!    let ratios = [ 1.0, 1.0, 2.0];
-//!    let exp = DiscreteFiniteRandomExperiment::new(omega.to_vec(), &ratios);
+//! use discrete_law::DiscreteFiniteRandomExperiment;
 //!
-//!    let rep: usize = 100_000;
-//!    println!("{rep} repetitions.\n");
-//!    println!("Fréquencies of A,B,C with probabilities 1/4,1/4,1/2 respectively, .");
-//!    exp.print_simulation(rep);
+//! let omega = ["A", "B", "C"];
+//! let ratios = [ 1.0, 1.0, 2.0];
+//! let exp = DiscreteFiniteRandomExperiment::new(omega.to_vec(), &ratios);
 //!
-//!    let omega: Vec<usize> = (1..7).collect();
-//!    let ratios =[ 1.0, 5.0, 5.0, 5.0, 5.0, 9.0];
-//!    let exp = DiscreteFiniteRandomExperiment::new(omega, &ratios);
+//! let rep: usize = 100_000;
+//! println!("{rep} repetitions.\n");
+//! println!("Fréquencies of A,B,C with probabilities 1/4,1/4,1/2 respectively, .");
+//! exp.print_simulation(rep);
 //!
-//!    println!("Fréquencies of 1 to 6  with probabilities 1/30,1/6,1/6,1/6,1/6,3/10 respectively.");
-//!    exp.print_simulation(100_000);
-//!}
+//! let omega: Vec<usize> = (1..7).collect();
+//! let ratios =[ 1.0, 5.0, 5.0, 5.0, 5.0, 9.0];
+//! let exp = DiscreteFiniteRandomExperiment::new(omega, &ratios);
+//!
+//! println!("Fréquencies of 1 to 6  with probabilities 1/30,1/6,1/6,1/6,1/6,3/10 respectively.");
+//! exp.print_simulation(100_000);
 //! ```
 //! 
 //! `exp` implements `Distribution` trait so you can use `exp.sample(rng)` to get a sample.
@@ -32,9 +32,35 @@ use iter_accumulate::IterAccumulate;
 use ordered_float::OrderedFloat;
 use rand::distr::{Distribution, Uniform};
 use std::collections::HashMap;
+use std::fmt;
 use std::hash::Hash;
 use rand::Rng;
 
+pub mod bandit;
+pub mod bayesian;
+pub mod causal;
+pub mod comparison;
+pub mod compound;
+pub mod decision;
+pub mod estimation;
+pub mod games;
+pub mod graphical;
+pub mod graphs;
+pub mod hmm;
+pub mod inequality;
+pub mod information;
+pub mod large_deviations;
+pub mod markov;
+pub mod mcmc;
+pub mod monte_carlo;
+pub mod patterns;
+pub mod processes;
+pub mod puzzles;
+pub mod records;
+pub mod resampling;
+pub mod statistics;
+pub mod variance_reduction;
+
 
 fn position(list: &[OrderedFloat<f64>], value: OrderedFloat<f64>) -> usize {
     match list.binary_search(&value) {
@@ -53,40 +79,222 @@ fn cdf_from (ratios: &[f64]) -> Vec<OrderedFloat<f64>> {
     //     cdf.push(new);
     //     last = new;
     // }
-   let mut cdf: Vec<OrderedFloat<f64>> = ratios.iter()
-        .accumulate(OrderedFloat(0.0), |acc, item| acc + *item)
+    // scale by the largest ratio first so that the total can't overflow
+    let scale = ratios.iter().copied().fold(0.0, f64::max);
+    let mut cdf: Vec<OrderedFloat<f64>> = ratios.iter()
+        .accumulate(OrderedFloat(0.0), |acc, item| acc + *item / scale)
         .collect();
 
     // normalization to get probability
-    let total = cdf[cdf.len()-1];
+    let Some(total) = cdf.last().copied() else {
+        return cdf;
+    };
     //for v in &mut cdf {
     //    *v = *v / total;
     //}
     cdf.iter_mut()
-        .for_each(|x| *x = *x/total);
+        .for_each(|x| *x /= total);
 
     cdf
 }
 
 
+/// Cumulative distribution built from `ratios`, exposed to the fuzz targets.
+#[cfg(fuzzing)]
+pub fn fuzz_cdf_from(ratios: &[f64]) -> Vec<f64> {
+    cdf_from(ratios).iter().map(|x| x.into_inner()).collect()
+}
+
+/// Errors raised when an experiment can't be built from the given weights.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiscreteExperimentError {
+    /// The sample space has no outcome.
+    EmptySampleSpace,
+    /// A weight is negative, infinite or NaN (index, weight).
+    InvalidWeight(usize, f64),
+    /// All weights are zero, no probability law can be derived.
+    ZeroTotalWeight,
+    /// The sample space and the weights don't have the same length (outcomes, weights).
+    LengthMismatch(usize, usize),
+}
+
+impl fmt::Display for DiscreteExperimentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiscreteExperimentError::EmptySampleSpace => write!(f, "sample space is empty"),
+            DiscreteExperimentError::InvalidWeight(i, w) =>
+                write!(f, "weight {} at index {} is not a non-negative number", w, i),
+            DiscreteExperimentError::ZeroTotalWeight => write!(f, "weights sum to zero"),
+            DiscreteExperimentError::LengthMismatch(n, m) => write!(f, "{} outcomes but {} weights", n, m),
+        }
+    }
+}
+
+impl std::error::Error for DiscreteExperimentError {}
+
+/// Errors raised by statistics comparing laws.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatError {
+    /// The laws don't have the same number of outcomes (lengths).
+    SupportMismatch(usize, usize),
+    /// The proposal law gives zero probability to an outcome (index) of positive probability.
+    UnsupportedOutcome(usize),
+    /// The target exceeds the bound times the proposal law at an outcome (index).
+    UnboundedTarget(usize),
+    /// The target is negative, infinite or NaN at an outcome (index).
+    InvalidTarget(usize),
+    /// The target is zero on every outcome, no draw is ever accepted.
+    ZeroTarget,
+    /// The bound is not a positive finite number.
+    InvalidBound(f64),
+}
+
+impl fmt::Display for StatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatError::SupportMismatch(a, b) => write!(f, "laws have different supports ({} and {} outcomes)", a, b),
+            StatError::UnsupportedOutcome(i) => write!(f, "outcome {} has positive probability but is never proposed", i),
+            StatError::UnboundedTarget(i) => write!(f, "target exceeds the bound times the proposal at outcome {}", i),
+            StatError::InvalidTarget(i) => write!(f, "target at outcome {} is not a non-negative number", i),
+            StatError::ZeroTarget => write!(f, "target is zero on every outcome"),
+            StatError::InvalidBound(m) => write!(f, "bound {} is not a positive number", m),
+        }
+    }
+}
+
+impl std::error::Error for StatError {}
+
+/// Errors raised by `information_projection`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectionError {
+    /// No law on the support has this mean: it is outside the range of the outcomes of positive probability.
+    InfeasibleMean(f64),
+}
+
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectionError::InfeasibleMean(m) => write!(f, "no law on the support has mean {}", m),
+        }
+    }
+}
+
+impl std::error::Error for ProjectionError {}
+
+fn check_weights(weights: &[f64]) -> Result<(), DiscreteExperimentError> {
+    if weights.is_empty() {
+        return Err(DiscreteExperimentError::EmptySampleSpace);
+    }
+    if let Some((i, w)) = weights.iter().enumerate().find(|(_, w)| !w.is_finite() || **w < 0.0) {
+        return Err(DiscreteExperimentError::InvalidWeight(i, *w));
+    }
+    if weights.iter().all(|w| *w == 0.0) {
+        return Err(DiscreteExperimentError::ZeroTotalWeight);
+    }
+    Ok(())
+}
+
+
+/// Total variation distance `1/2 * sum |p_i - q_i|` between two laws on the same outcomes.
+pub fn total_variation_distance(p: &[f64], q: &[f64]) -> f64 {
+    assert_eq!(p.len(), q.len(), "laws must have the same length");
+    0.5 * p.iter().zip(q).map(|(a, b)| (a - b).abs()).sum::<f64>()
+}
+
+/// Complementary error function, Chebyshev fit with relative error below 1.2e-7.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368 + t * (0.37409196 + t * (0.09678418 + t * (-0.18628806
+        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * poly.exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Cumulative distribution function of the standard normal law.
+pub fn standard_normal_cdf(z: f64) -> f64 {
+    0.5 * erfc(-z / std::f64::consts::SQRT_2)
+}
+
+
 /// Discrete distribution struct
 /// Contains the probability law and it's cumulative distribution.
 /// The cumulative distribution contains OrderedFloat because of use of binary_search to find the index from the value.
 #[derive(Debug)]
 pub struct DiscreteFiniteDistribution {
-    _law: Vec<f64>,
+    law: Vec<f64>,
     cdf:  Vec<OrderedFloat<f64>>
 }
 
 /// Distribution for the probability law.
 impl DiscreteFiniteDistribution {
+    /// Distribution from the weights `law`, which are not checked: invalid weights give an invalid
+    /// cumulative distribution. `try_new` is the checked constructor.
     pub fn new( law: &[f64] ) -> Self {
         DiscreteFiniteDistribution { 
-            _law: law.to_vec(), 
+            law: law.to_vec(), 
             cdf: cdf_from( law)
         }
     }
 
+    /// Distribution from weights that are checked: non empty, finite, non negative and not all zero.
+    pub fn try_new(law: &[f64]) -> Result<Self, DiscreteExperimentError> {
+        check_weights(law)?;
+        Ok(Self::new(law))
+    }
+
+    /// Law divided by its sum so that it adds up to 1.0.
+    pub fn normalize(law: &[f64]) -> Vec<f64> {
+        let total: f64 = law.iter().sum();
+        law.iter().map(|x| x / total).collect()
+    }
+
+    /// Whether the law sums to 1.0 up to `eps`.
+    pub fn is_normalized(law: &[f64], eps: f64) -> bool {
+        (law.iter().sum::<f64>() - 1.0).abs() < eps
+    }
+
+    /// Normalize the stored law in place and rebuild the cumulative distribution.
+    pub fn renormalize(&mut self) {
+        self.law = Self::normalize(&self.law);
+        self.cdf = cdf_from(&self.law);
+    }
+
+    /// Probability of each outcome (normalized law).
+    pub fn probabilities(&self) -> Vec<f64> {
+        Self::normalize(&self.law)
+    }
+
+    /// Cumulative distribution: `cdf[i]` is the probability of the outcome indices up to `i`.
+    pub fn cdf(&self) -> Vec<f64> {
+        self.cdf.iter().map(|x| x.into_inner()).collect()
+    }
+
+    /// Number of outcomes.
+    pub fn len(&self) -> usize {
+        self.law.len()
+    }
+
+    /// Whether the distribution has no outcome.
+    pub fn is_empty(&self) -> bool {
+        self.law.is_empty()
+    }
+
+    /// Quantile function: the first outcome index whose cumulative probability reaches `u` in `[0, 1]`.
+    pub fn quantile(&self, u: f64) -> usize {
+        position(&self.cdf, OrderedFloat(u)).min(self.len() - 1)
+    }
+
+    /// Frequencies of each outcome index over `n` draws.
+    pub(crate) fn empirical_law<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Vec<f64> {
+        let mut counts = vec![0usize; self.len()];
+        for _ in 0..n {
+            counts[self.sample(rng)] += 1;
+        }
+        counts.iter().map(|c| *c as f64 / n as f64).collect()
+    }
+
 //    pub fn sample(&self) -> usize {
 //        let u: OrderedFloat<f64> = OrderedFloat(random());
 //        position(&self.cdf, u)
@@ -96,8 +304,7 @@ impl DiscreteFiniteDistribution {
 
 impl Distribution<usize> for DiscreteFiniteDistribution {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
-        let u: OrderedFloat<f64> = OrderedFloat(rng.sample(Uniform::new(0.0, 1.0).unwrap()));
-        position(&self.cdf, u)
+        self.quantile(rng.sample(Uniform::new(0.0, 1.0).unwrap()))
     }
 }
 
@@ -110,6 +317,7 @@ pub struct DiscreteFiniteRandomExperiment<T> {
 
 /// Create the experiment from space sample `omega` and `law`
 impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Experiment with one weight per outcome, neither checked (see `try_new`).
     pub fn new( omega: Vec<T>, law: &[f64]) -> Self {
         DiscreteFiniteRandomExperiment {
             omega,
@@ -117,11 +325,42 @@ impl<T> DiscreteFiniteRandomExperiment<T> {
         }
     }
 
+    /// Experiment with checked weights, one per outcome (see `DiscreteFiniteDistribution::try_new`).
+    pub fn try_new(omega: Vec<T>, law: &[f64]) -> Result<Self, DiscreteExperimentError> {
+        if omega.len() != law.len() {
+            return Err(DiscreteExperimentError::LengthMismatch(omega.len(), law.len()));
+        }
+        Ok(DiscreteFiniteRandomExperiment { omega, distribution: DiscreteFiniteDistribution::try_new(law)? })
+    }
+
+    /// Experiment from `(outcome, count)` pairs, the law is proportional to the counts.
+    pub fn from_counts(counts: impl IntoIterator<Item = (T, usize)>) -> Self {
+        let (omega, weights): (Vec<T>, Vec<f64>) = counts.into_iter()
+            .map(|(o, c)| (o, c as f64))
+            .unzip();
+        DiscreteFiniteRandomExperiment::new(omega, &weights)
+    }
+
 //    pub fn sample(&self) -> &T {
 //        &self.omega[self.distribution.sample()]
 //    }
 }
 
+/// Experiments over integer ranges with weights given by a function.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Experiment over `0..n` where outcome `k` has weight `weight_fn(k)`.
+    pub fn from_fn(n: usize, weight_fn: impl Fn(usize) -> f64) -> Result<Self, DiscreteExperimentError> {
+        Self::from_fn_range(0, n, weight_fn)
+    }
+
+    /// Experiment over `start..end` where outcome `k` has weight `weight_fn(k)`.
+    pub fn from_fn_range(start: usize, end: usize, weight_fn: impl Fn(usize) -> f64) -> Result<Self, DiscreteExperimentError> {
+        let omega: Vec<usize> = (start..end).collect();
+        let weights: Vec<f64> = omega.iter().map(|k| weight_fn(*k)).collect();
+        DiscreteFiniteRandomExperiment::try_new(omega, &weights)
+    }
+}
+
 impl<T: Clone> Distribution<T> for DiscreteFiniteRandomExperiment<T>
 {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> T {
@@ -129,6 +368,86 @@ impl<T: Clone> Distribution<T> for DiscreteFiniteRandomExperiment<T>
     }
 }
 
+/// Counts of each outcome over repeated draws of an experiment, see `simulate`.
+#[derive(Debug, Clone)]
+pub struct SimulationResult<T> {
+    pub omega: Vec<T>,
+    /// Number of draws of each outcome of `omega`.
+    pub counts: Vec<usize>,
+}
+
+impl<T> SimulationResult<T> {
+    /// Total number of draws.
+    pub fn n(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Empirical frequency of each outcome.
+    pub fn frequencies(&self) -> Vec<f64> {
+        let n = self.n() as f64;
+        self.counts.iter().map(|c| *c as f64 / n).collect()
+    }
+}
+
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Count the outcomes of `n` draws.
+    pub fn simulate<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> SimulationResult<T> {
+        let mut counts = vec![0; self.omega.len()];
+        for _ in 0..n {
+            counts[Distribution::sample(&self.distribution, rng)] += 1;
+        }
+        SimulationResult { omega: self.omega.clone(), counts }
+    }
+}
+
+/// Draws without replacement.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Draw `k` distinct outcomes, each draw following the law restricted to the outcomes not drawn yet.
+    /// Outcomes with zero probability are never drawn.
+    pub fn sample_without_replacement<R: Rng + ?Sized>(&self, rng: &mut R, k: usize) -> Vec<T> {
+        // Efraimidis-Spirakis: the k largest keys u^(1/w) give a sequential weighted draw.
+        let mut keys: Vec<(f64, usize)> = self.distribution.law.iter()
+            .enumerate()
+            .filter(|(_, w)| **w > 0.0)
+            .map(|(i, w)| (rng.random::<f64>().powf(1.0 / w), i))
+            .collect();
+        assert!(k <= keys.len(), "not enough outcomes to draw without replacement");
+        keys.sort_by(|a, b| b.0.total_cmp(&a.0));
+        keys[..k].iter().map(|(_, i)| self.omega[*i].clone()).collect()
+    }
+}
+
+/// Moments of experiments with numeric outcomes.
+impl<T: Clone + Into<f64>> DiscreteFiniteRandomExperiment<T> {
+    /// Expected value `sum p_i * omega_i`.
+    pub fn expected_value(&self) -> f64 {
+        self.omega.iter()
+            .zip(self.distribution.probabilities())
+            .map(|(x, p)| p * x.clone().into())
+            .sum()
+    }
+
+    /// Variance `sum p_i * (omega_i - mean)^2`.
+    pub fn variance(&self) -> f64 {
+        let mean = self.expected_value();
+        self.omega.iter()
+            .zip(self.distribution.probabilities())
+            .map(|(x, p)| p * (x.clone().into() - mean).powi(2))
+            .sum()
+    }
+}
+
+impl<T: PartialEq> DiscreteFiniteRandomExperiment<T> {
+    /// Probability of `outcome`, summed over the outcomes of `omega` equal to it.
+    pub fn probability_of(&self, outcome: &T) -> f64 {
+        self.omega.iter()
+            .zip(self.distribution.probabilities())
+            .filter(|(o, _)| *o == outcome)
+            .map(|(_, p)| p)
+            .sum()
+    }
+}
+
 /// utility to print frequencies of values in experiment repetition.
 impl<T: std::fmt::Debug + Eq + Hash + Clone> DiscreteFiniteRandomExperiment<T> {
     pub fn print_simulation (&self, n: usize) {
@@ -150,11 +469,13 @@ impl<T: std::fmt::Debug + Eq + Hash + Clone> DiscreteFiniteRandomExperiment<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
 
     #[test]
     fn distribution_check() {
         let piped_dice = 
-                DiscreteFiniteRandomExperiment::new(vec![1,2,3,4,5,6], &vec![1.0,4.0,4.0,4.0,4.0,7.0]);
+                DiscreteFiniteRandomExperiment::new(vec![1,2,3,4,5,6], &[1.0,4.0,4.0,4.0,4.0,7.0]);
         assert!(piped_dice.distribution.cdf[0] - OrderedFloat(1.0/24.0) <= OrderedFloat(f64::EPSILON));
         assert!(piped_dice.distribution.cdf[1] - OrderedFloat(5.0/24.0) <= OrderedFloat(f64::EPSILON));
         assert!(piped_dice.distribution.cdf[2] - OrderedFloat(9.0/24.0) <= OrderedFloat(f64::EPSILON));
@@ -164,4 +485,74 @@ mod tests {
         let r = piped_dice.sample(&mut rand::rng());
         assert!( piped_dice.omega.contains(&r) );     
      }
+
+    #[test]
+    fn from_fn_check() {
+        let exp = DiscreteFiniteRandomExperiment::from_fn(4, |k| (k+1) as f64).unwrap();
+        let hand = DiscreteFiniteRandomExperiment::new(vec![0,1,2,3], &[1.0,2.0,3.0,4.0]);
+        assert_eq!(exp.omega, hand.omega);
+        assert_eq!(exp.distribution.cdf, hand.distribution.cdf);
+
+        let exp = DiscreteFiniteRandomExperiment::from_fn_range(2, 5, |k| k as f64).unwrap();
+        assert_eq!(exp.omega, vec![2,3,4]);
+        assert_eq!(DiscreteFiniteRandomExperiment::from_fn(3, |_| 0.0).unwrap_err(),
+                   DiscreteExperimentError::ZeroTotalWeight);
+        assert_eq!(DiscreteFiniteRandomExperiment::from_fn(3, |k| 1.0 - k as f64).unwrap_err(),
+                   DiscreteExperimentError::InvalidWeight(2, -1.0));
+        assert_eq!(DiscreteFiniteRandomExperiment::from_fn(2, |_| f64::INFINITY).unwrap_err(),
+                   DiscreteExperimentError::InvalidWeight(0, f64::INFINITY));
+        assert!(DiscreteFiniteRandomExperiment::from_fn(2, |_| f64::NAN).is_err());
+        assert_eq!(DiscreteFiniteRandomExperiment::from_fn(0, |_| 1.0).unwrap_err(),
+                   DiscreteExperimentError::EmptySampleSpace);
+    }
+
+    #[test]
+    fn try_new_check() {
+        let d = DiscreteFiniteDistribution::try_new(&[1.0, 3.0]).unwrap();
+        assert_eq!(d.cdf(), vec![0.25, 1.0]);
+        assert_eq!(DiscreteFiniteDistribution::try_new(&[1.0, f64::INFINITY]).unwrap_err(),
+                   DiscreteExperimentError::InvalidWeight(1, f64::INFINITY));
+        assert_eq!(DiscreteFiniteRandomExperiment::try_new(vec!['a'], &[1.0, 2.0]).unwrap_err(),
+                   DiscreteExperimentError::LengthMismatch(1, 2));
+
+        // the total of huge weights overflows, not their cumulative distribution
+        let huge = DiscreteFiniteDistribution::try_new(&[f64::MAX, f64::MAX]).unwrap();
+        assert_eq!(huge.cdf(), vec![0.5, 1.0]);
+        assert!(DiscreteFiniteDistribution::new(&[]).cdf().is_empty());
+    }
+
+    #[test]
+    fn normalize_check() {
+        let law = DiscreteFiniteDistribution::normalize(&[2.0, 2.0, 4.0]);
+        assert_eq!(law, vec![0.25, 0.25, 0.5]);
+        assert!(DiscreteFiniteDistribution::is_normalized(&[0.25, 0.25, 0.5], 1e-10));
+        assert!(!DiscreteFiniteDistribution::is_normalized(&[2.0, 2.0, 4.0], 1e-10));
+
+        let mut d = DiscreteFiniteDistribution::new(&[2.0, 2.0, 4.0]);
+        d.renormalize();
+        assert_eq!(d.law, vec![0.25, 0.25, 0.5]);
+        assert_eq!(d.cdf, vec![OrderedFloat(0.25), OrderedFloat(0.5), OrderedFloat(1.0)]);
+    }
+
+    #[test]
+    fn moments_check() {
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1,2,3,4,5,6], &[1.0; 6]);
+        assert!((dice.expected_value() - 3.5).abs() < 1e-12);
+        assert!((dice.variance() - 35.0 / 12.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sample_without_replacement_check() {
+        let mut rng = StdRng::seed_from_u64(125);
+        let exp = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4], &[1.0, 0.0, 2.0, 3.0]);
+        let mut draws = exp.sample_without_replacement(&mut rng, 3);
+        draws.sort();
+        assert_eq!(draws, vec![1, 3, 4]);
+
+        // first draw follows the law
+        let first_is_4 = (0..10_000)
+            .filter(|_| exp.sample_without_replacement(&mut rng, 1)[0] == 4)
+            .count();
+        assert!((first_is_4 as f64 / 10_000.0 - 0.5).abs() < 0.03);
+    }
 }
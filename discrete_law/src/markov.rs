@@ -0,0 +1,303 @@
+//! Finite Markov chains whose transitions are discrete finite distributions.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::statistics::mean_and_variance;
+use crate::{total_variation_distance, DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment};
+
+/// Markov chain over `states`.
+/// Row `i` of the transition matrix is the distribution of the next state index from state `i`.
+#[derive(Debug)]
+pub struct MarkovChain<S> {
+    pub states: Vec<S>,
+    pub transitions: Vec<DiscreteFiniteDistribution>,
+}
+
+/// Result of `mixing_time_simulation`.
+#[derive(Debug, Clone)]
+pub struct MixingTimeResult {
+    /// First step where the total variation distance is below epsilon, if reached.
+    pub mixing_time: Option<usize>,
+    /// Total variation distance to the stationary distribution at steps `0..=max_steps`
+    /// (or up to the mixing time).
+    pub tv_distances: Vec<f64>,
+}
+
+impl<S> MarkovChain<S> {
+    /// Create the chain from its states and transition matrix (rows are normalized).
+    pub fn new(states: Vec<S>, matrix: &[Vec<f64>]) -> Self {
+        assert_eq!(states.len(), matrix.len(), "one transition row per state is required");
+        assert!(matrix.iter().all(|row| row.len() == states.len()), "transition matrix must be square");
+        MarkovChain {
+            states,
+            transitions: matrix.iter().map(|row| DiscreteFiniteDistribution::new(row)).collect(),
+        }
+    }
+
+    /// Number of states.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Whether the chain has no state.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Normalized transition matrix.
+    pub fn transition_matrix(&self) -> Vec<Vec<f64>> {
+        self.transitions.iter().map(|row| row.probabilities()).collect()
+    }
+
+    /// Draw the next state index from state index `state`.
+    pub fn step<R: Rng + ?Sized>(&self, rng: &mut R, state: usize) -> usize {
+        self.transitions[state].sample(rng)
+    }
+
+    /// Run the chain `n_steps` from `start_state`, returning the visited state indices (start included).
+    pub fn simulate<R: Rng + ?Sized>(&self, rng: &mut R, start_state: usize, n_steps: usize) -> Vec<usize> {
+        let mut path = Vec::with_capacity(n_steps + 1);
+        let mut state = start_state;
+        path.push(state);
+        for _ in 0..n_steps {
+            state = self.step(rng, state);
+            path.push(state);
+        }
+        path
+    }
+
+    /// Stationary distribution by power iteration.
+    /// The lazy chain (I+P)/2 is iterated so periodic chains converge too.
+    pub fn stationary_distribution(&self) -> Vec<f64> {
+        let p = self.transition_matrix();
+        let n = self.len();
+        let mut pi = vec![1.0 / n as f64; n];
+        for _ in 0..100_000 {
+            let mut next = vec![0.0; n];
+            for i in 0..n {
+                for j in 0..n {
+                    next[j] += pi[i] * p[i][j];
+                }
+            }
+            next.iter_mut().zip(&pi).for_each(|(x, y)| *x = 0.5 * (*x + y));
+            let delta: f64 = next.iter().zip(&pi).map(|(a, b)| (a - b).abs()).sum();
+            pi = next;
+            if delta < 1e-15 {
+                break;
+            }
+        }
+        pi
+    }
+
+    /// Estimate the mixing time from `start_state`.
+    ///
+    /// `repetitions` chains are run together, the empirical law of their state at step `t`
+    /// is compared to the stationary distribution with the total variation distance.
+    pub fn mixing_time_simulation<R: Rng>(&self, rng: &mut R, start_state: usize, epsilon: f64, max_steps: usize, repetitions: usize) -> MixingTimeResult {
+        let pi = self.stationary_distribution();
+        let mut chains = vec![start_state; repetitions];
+        let mut tv_distances = Vec::new();
+
+        for t in 0..=max_steps {
+            if t > 0 {
+                chains.iter_mut().for_each(|s| *s = self.step(rng, *s));
+            }
+            let mut law = vec![0.0; self.len()];
+            chains.iter().for_each(|s| law[*s] += 1.0 / repetitions as f64);
+            let tv = total_variation_distance(&law, &pi);
+            tv_distances.push(tv);
+            if tv < epsilon {
+                return MixingTimeResult { mixing_time: Some(t), tv_distances };
+            }
+        }
+        MixingTimeResult { mixing_time: None, tv_distances }
+    }
+}
+
+
+/// Result of `simulate_absorption`.
+#[derive(Debug, Clone)]
+pub struct AbsorptionResult {
+    /// Mean number of steps before absorption (chains not absorbed within `max_steps` excluded).
+    pub mean_absorption_time: f64,
+    pub variance_absorption_time: f64,
+    /// Probability of absorption in each of the absorbing states, in the given order.
+    pub absorption_probabilities: Vec<f64>,
+}
+
+/// Inverse of a square matrix by Gauss-Jordan elimination with partial pivoting.
+fn invert(matrix: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut r = row.clone();
+            r.extend((0..n).map(|j| if i == j { 1.0 } else { 0.0 }));
+            r
+        })
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs())).unwrap();
+        assert!(a[pivot][col].abs() > 1e-300, "matrix is singular");
+        a.swap(col, pivot);
+        let d = a[col][col];
+        a[col].iter_mut().for_each(|x| *x /= d);
+        for row in 0..n {
+            if row != col {
+                let f = a[row][col];
+                let pivot_row = a[col].clone();
+                a[row].iter_mut().zip(pivot_row).for_each(|(x, y)| *x -= f * y);
+            }
+        }
+    }
+    a.into_iter().map(|row| row[n..].to_vec()).collect()
+}
+
+/// Absorbing chains.
+impl<S> MarkovChain<S> {
+    /// Fundamental matrix `N = (I - Q)^-1` where `Q` is the transition matrix restricted to
+    /// the transient states (all states not in `absorbing_states`, in increasing order).
+    pub fn fundamental_matrix(&self, absorbing_states: &[usize]) -> Vec<Vec<f64>> {
+        let p = self.transition_matrix();
+        let transient: Vec<usize> = (0..self.len()).filter(|s| !absorbing_states.contains(s)).collect();
+        let i_minus_q: Vec<Vec<f64>> = transient.iter()
+            .map(|i| transient.iter().map(|j| if i == j { 1.0 } else { 0.0 } - p[*i][*j]).collect())
+            .collect();
+        invert(&i_minus_q)
+    }
+
+    /// Expected absorption time and absorption probabilities from the transient `start_state`,
+    /// as rows of `N * 1` and `N * R`.
+    pub fn theoretical_absorption(&self, start_state: usize, absorbing_states: &[usize]) -> (f64, Vec<f64>) {
+        let p = self.transition_matrix();
+        let transient: Vec<usize> = (0..self.len()).filter(|s| !absorbing_states.contains(s)).collect();
+        let row = transient.iter().position(|s| *s == start_state).expect("start state must be transient");
+        let n = &self.fundamental_matrix(absorbing_states)[row];
+        let time = n.iter().sum();
+        let probabilities = absorbing_states.iter()
+            .map(|a| transient.iter().zip(n).map(|(t, nt)| nt * p[*t][*a]).sum())
+            .collect();
+        (time, probabilities)
+    }
+
+    /// Run `repetitions` chains from `start_state` until they hit one of `absorbing_states`.
+    pub fn simulate_absorption<R: Rng>(&self, rng: &mut R, start_state: usize, absorbing_states: &[usize], max_steps: usize, repetitions: usize) -> AbsorptionResult {
+        let mut times = Vec::with_capacity(repetitions);
+        let mut absorbed = vec![0usize; absorbing_states.len()];
+        for _ in 0..repetitions {
+            let mut state = start_state;
+            for t in 0..=max_steps {
+                if let Some(a) = absorbing_states.iter().position(|s| *s == state) {
+                    absorbed[a] += 1;
+                    times.push(t as f64);
+                    break;
+                }
+                state = self.step(rng, state);
+            }
+        }
+
+        let (mean, variance) = mean_and_variance(&times);
+        AbsorptionResult {
+            mean_absorption_time: mean,
+            variance_absorption_time: variance,
+            absorption_probabilities: absorbed.iter().map(|a| *a as f64 / repetitions as f64).collect(),
+        }
+    }
+}
+
+/// PageRank by simulation, `self.omega` is the set of pages.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Random surfer started from a page drawn from `self`: with probability `damping` it follows
+    /// a link drawn from `transition_rows[page]`, otherwise it teleports to a uniform page.
+    /// Return the fraction of the `n_steps` steps spent at each page.
+    pub fn simulate_pagerank<R: Rng>(&self, transition_rows: Vec<DiscreteFiniteDistribution>, rng: &mut R, n_steps: usize, damping: f64) -> Vec<f64> {
+        let n = self.omega.len();
+        assert_eq!(transition_rows.len(), n, "one link distribution per page is required");
+        let mut visits = vec![0usize; n];
+        let mut page = self.distribution.sample(rng);
+        for _ in 0..n_steps {
+            page = if rng.random::<f64>() < damping {
+                transition_rows[page].sample(rng)
+            } else {
+                rng.random_range(0..n)
+            };
+            visits[page] += 1;
+        }
+        visits.iter().map(|v| *v as f64 / n_steps as f64).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn mixing_time_check() {
+        let mut rng = StdRng::seed_from_u64(104);
+        // P = I/2 + J/6 : TV distance from a point mass at step t is 2/3 * (1/2)^t.
+        let chain = MarkovChain::new(vec!['a', 'b', 'c'], &[
+            vec![4.0, 1.0, 1.0],
+            vec![1.0, 4.0, 1.0],
+            vec![1.0, 1.0, 4.0],
+        ]);
+        let pi = chain.stationary_distribution();
+        assert!(pi.iter().all(|x| (x - 1.0 / 3.0).abs() < 1e-9));
+
+        let res = chain.mixing_time_simulation(&mut rng, 0, 0.06, 50, 20_000);
+        assert_eq!(res.mixing_time, Some(4));
+        assert!((res.tv_distances[0] - 2.0 / 3.0).abs() < 1e-12);
+        assert!((res.tv_distances[1] - 1.0 / 3.0).abs() < 0.02);
+
+        let res = chain.mixing_time_simulation(&mut rng, 0, 0.0, 3, 100);
+        assert_eq!(res.mixing_time, None);
+        assert_eq!(res.tv_distances.len(), 4);
+    }
+
+    #[test]
+    fn pagerank_check() {
+        let mut rng = StdRng::seed_from_u64(118);
+        let pages = DiscreteFiniteRandomExperiment::new(vec!["a", "b", "c"], &[1.0, 1.0, 1.0]);
+        let links = vec![
+            DiscreteFiniteDistribution::new(&[0.0, 1.0, 1.0]),
+            DiscreteFiniteDistribution::new(&[1.0, 0.0, 1.0]),
+            DiscreteFiniteDistribution::new(&[1.0, 1.0, 0.0]),
+        ];
+        let rank = pages.simulate_pagerank(links, &mut rng, 100_000, 0.85);
+        assert!(rank.iter().all(|r| (r - 1.0 / 3.0).abs() < 0.01));
+
+        // page "c" only links to "a", "b" links to "c": "b" gets visited less
+        let links = vec![
+            DiscreteFiniteDistribution::new(&[0.0, 1.0, 1.0]),
+            DiscreteFiniteDistribution::new(&[0.0, 0.0, 1.0]),
+            DiscreteFiniteDistribution::new(&[1.0, 0.0, 0.0]),
+        ];
+        let rank = pages.simulate_pagerank(links, &mut rng, 100_000, 0.85);
+        assert!(rank[1] < rank[0] && rank[1] < rank[2]);
+    }
+
+    #[test]
+    fn absorption_check() {
+        let mut rng = StdRng::seed_from_u64(130);
+        // fair gambler's ruin on 0..=5
+        let matrix: Vec<Vec<f64>> = (0..=5)
+            .map(|i| (0..=5).map(|j| match i {
+                0 | 5 => if i == j { 1.0 } else { 0.0 },
+                _ => if j + 1 == i || j == i + 1 { 0.5 } else { 0.0 },
+            }).collect())
+            .collect();
+        let chain = MarkovChain::new((0..=5).collect(), &matrix);
+
+        let (time, probabilities) = chain.theoretical_absorption(2, &[0, 5]);
+        assert!((time - 6.0).abs() < 1e-9);
+        assert!((probabilities[0] - 0.6).abs() < 1e-9);
+        assert!((probabilities[1] - 0.4).abs() < 1e-9);
+
+        let res = chain.simulate_absorption(&mut rng, 2, &[0, 5], 10_000, 20_000);
+        assert!((res.mean_absorption_time - 6.0).abs() < 0.15);
+        assert!((res.absorption_probabilities[1] - 0.4).abs() < 0.01);
+        assert!(res.variance_absorption_time > 0.0);
+    }
+}
@@ -0,0 +1,249 @@
+//! Markov chain Monte Carlo samplers over finite sample spaces.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Conditional law of a `Y` given an `X`.
+pub type Conditional<X, Y> = Box<dyn Fn(&X) -> DiscreteFiniteRandomExperiment<Y>>;
+
+/// Data augmentation chain alternating a latent variable `T` and a parameter `P`:
+/// the latent variable is drawn given the parameter, then the parameter from its full conditional
+/// given the latent variable.
+pub struct DataAugmentationChain<T, P> {
+    latent_given_parameter: Conditional<P, T>,
+    parameter_given_latent: Conditional<T, P>,
+    /// Current parameter.
+    pub parameter: P,
+}
+
+impl<T: Clone, P: Clone> DataAugmentationChain<T, P> {
+    pub fn new(
+        latent_given_parameter: impl Fn(&P) -> DiscreteFiniteRandomExperiment<T> + 'static,
+        parameter_given_latent: impl Fn(&T) -> DiscreteFiniteRandomExperiment<P> + 'static,
+        initial_parameter: P,
+    ) -> Self {
+        DataAugmentationChain {
+            latent_given_parameter: Box::new(latent_given_parameter),
+            parameter_given_latent: Box::new(parameter_given_latent),
+            parameter: initial_parameter,
+        }
+    }
+
+    /// One sweep: draw the latent variable, then update the parameter. Return both.
+    pub fn step<R: Rng>(&mut self, rng: &mut R) -> (T, P) {
+        let latent = (self.latent_given_parameter)(&self.parameter).sample(rng);
+        self.parameter = (self.parameter_given_latent)(&latent).sample(rng);
+        (latent, self.parameter.clone())
+    }
+}
+
+
+/// Metropolis-Hastings with `self` as independent proposal.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Sample the law proportional to `exp(log_likelihood)`.
+    ///
+    /// A proposal `y` from the current state `x` is accepted with probability
+    /// `min(1, exp(ll(y) - ll(x)) * q(x) / q(y))`, which is `min(1, exp(ll(y) - ll(x)))`
+    /// for a uniform proposal. The first `burn_in` states are dropped, `n_samples` are returned.
+    pub fn mh_sample<R: Rng, LL: Fn(&T) -> f64>(&self, rng: &mut R, log_likelihood: LL, n_samples: usize, burn_in: usize) -> Vec<T> {
+        let ln_q: Vec<f64> = self.distribution.probabilities().iter().map(|q| q.ln()).collect();
+        let ll: Vec<f64> = self.omega.iter().map(log_likelihood).collect();
+
+        let mut current = self.distribution.sample(rng);
+        let mut samples = Vec::with_capacity(n_samples);
+        for t in 0..burn_in + n_samples {
+            let proposed = self.distribution.sample(rng);
+            let log_ratio = ll[proposed] - ll[current] + ln_q[current] - ln_q[proposed];
+            if log_ratio >= 0.0 || rng.random::<f64>() < log_ratio.exp() {
+                current = proposed;
+            }
+            if t >= burn_in {
+                samples.push(self.omega[current].clone());
+            }
+        }
+        samples
+    }
+}
+
+/// Gibbs sampler for a product distribution: variable `i` is refreshed from `conditionals[i]`.
+pub struct GibbsSampler<T> {
+    conditionals: Vec<DiscreteFiniteRandomExperiment<T>>,
+    /// Current value of every variable, empty before the first sweep.
+    pub state: Vec<T>,
+}
+
+impl<T: Clone> GibbsSampler<T> {
+    pub fn new(conditionals: Vec<DiscreteFiniteRandomExperiment<T>>) -> Self {
+        GibbsSampler { conditionals, state: Vec::new() }
+    }
+
+    /// Update every variable in sequence.
+    fn sweep<R: Rng>(&mut self, rng: &mut R) {
+        for (i, conditional) in self.conditionals.iter().enumerate() {
+            let value = conditional.sample(rng);
+            if i < self.state.len() {
+                self.state[i] = value;
+            } else {
+                self.state.push(value);
+            }
+        }
+    }
+
+    /// Drop `burn_in` sweeps, then return the state after each of the next `n_samples` sweeps.
+    pub fn sample<R: Rng>(&mut self, rng: &mut R, n_samples: usize, burn_in: usize) -> Vec<Vec<T>> {
+        for _ in 0..burn_in {
+            self.sweep(rng);
+        }
+        (0..n_samples)
+            .map(|_| {
+                self.sweep(rng);
+                self.state.clone()
+            })
+            .collect()
+    }
+}
+
+/// Parallel tempering over the outcome indices.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Run one Metropolis chain per temperature, the chain at temperature `T` targeting the law
+    /// proportional to `p_i^(1 / T)` with a proposal moving to a neighbouring index `i - 1` or `i + 1`.
+    /// Every `swap_interval` steps the states of adjacent temperatures are exchanged with probability
+    /// `min(1, exp((1 / T_k - 1 / T_(k+1)) (ln p(x_(k+1)) - ln p(x_k))))`. Return the `n_steps` states
+    /// of the coldest chain, which follows `p` while the hot chains carry it across low probability regions.
+    pub fn parallel_tempering<R: Rng>(&self, rng: &mut R, temperatures: &[f64], n_steps: usize, swap_interval: usize) -> Vec<usize> {
+        assert!(!temperatures.is_empty(), "at least one temperature is required");
+        let ln_p: Vec<f64> = self.distribution.probabilities().iter().map(|p| p.ln()).collect();
+        let n = ln_p.len() as isize;
+        let mut order: Vec<usize> = (0..temperatures.len()).collect();
+        order.sort_by(|a, b| temperatures[*a].total_cmp(&temperatures[*b]));
+        let betas: Vec<f64> = order.iter().map(|k| 1.0 / temperatures[*k]).collect();
+
+        let mut states: Vec<usize> = betas.iter().map(|_| self.distribution.sample(rng)).collect();
+        let mut samples = Vec::with_capacity(n_steps);
+        for step in 1..=n_steps {
+            for (state, beta) in states.iter_mut().zip(&betas) {
+                let proposed = *state as isize + if rng.random::<bool>() { 1 } else { -1 };
+                if (0..n).contains(&proposed) {
+                    let log_ratio = beta * (ln_p[proposed as usize] - ln_p[*state]);
+                    if log_ratio >= 0.0 || rng.random::<f64>() < log_ratio.exp() {
+                        *state = proposed as usize;
+                    }
+                }
+            }
+            if swap_interval > 0 && step % swap_interval == 0 {
+                for k in 0..states.len() - 1 {
+                    let log_ratio = (betas[k] - betas[k + 1]) * (ln_p[states[k + 1]] - ln_p[states[k]]);
+                    if log_ratio >= 0.0 || rng.random::<f64>() < log_ratio.exp() {
+                        states.swap(k, k + 1);
+                    }
+                }
+            }
+            samples.push(states[0]);
+        }
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn data_augmentation_check() {
+        let mut rng = StdRng::seed_from_u64(139);
+        // Beta(1,1)-Binomial(10) with the parameter on a grid: X is close to uniform on 0..=10.
+        const N: usize = 10;
+        let grid: Vec<f64> = (0..50).map(|i| (i as f64 + 0.5) / 50.0).collect();
+        let binomial = |theta: &f64| {
+            let mut c = 1.0;
+            let law: Vec<f64> = (0..=N).map(|k| {
+                if k > 0 {
+                    c *= (N - k + 1) as f64 / k as f64;
+                }
+                c * theta.powi(k as i32) * (1.0 - theta).powi((N - k) as i32)
+            }).collect();
+            DiscreteFiniteRandomExperiment::new((0..=N).collect(), &law)
+        };
+        let posterior_grid = grid.clone();
+        let posterior = move |x: &usize| {
+            let law: Vec<f64> = posterior_grid.iter()
+                .map(|t| t.powi(*x as i32) * (1.0 - t).powi((N - x) as i32))
+                .collect();
+            DiscreteFiniteRandomExperiment::new(posterior_grid.clone(), &law)
+        };
+
+        let mut chain = DataAugmentationChain::new(binomial, posterior, 0.5);
+        let mut counts = [0usize; N + 1];
+        let mut theta_sum = 0.0;
+        let n = 20_000;
+        for _ in 0..n {
+            let (x, theta) = chain.step(&mut rng);
+            counts[x] += 1;
+            theta_sum += theta;
+        }
+        assert!(counts.iter().all(|c| (*c as f64 / n as f64 - 1.0 / 11.0).abs() < 0.02));
+        assert!((theta_sum / n as f64 - 0.5).abs() < 0.03);
+    }
+
+    #[test]
+    fn metropolis_hastings_check() {
+        let mut rng = StdRng::seed_from_u64(142);
+        let uniform = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2, 3], &[1.0; 4]);
+
+        let samples = uniform.mh_sample(&mut rng, |x| if *x == 2 { 0.0 } else { f64::NEG_INFINITY }, 1_000, 100);
+        assert_eq!(samples.len(), 1_000);
+        assert!(samples.iter().all(|x| *x == 2));
+
+        // target proportional to 1, 2, 3, 4 with a non uniform proposal
+        let proposal = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2, 3], &[4.0, 3.0, 2.0, 1.0]);
+        let samples = proposal.mh_sample(&mut rng, |x| ((*x + 1) as f64).ln(), 50_000, 1_000);
+        for k in 0..4 {
+            let freq = samples.iter().filter(|x| **x == k).count() as f64 / 50_000.0;
+            assert!((freq - (k + 1) as f64 / 10.0).abs() < 0.015);
+        }
+    }
+
+    #[test]
+    fn gibbs_sampler_check() {
+        let mut rng = StdRng::seed_from_u64(143);
+        let coin = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.3, 0.7]);
+        let die = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3], &[1.0, 1.0, 2.0]);
+        let mut gibbs = GibbsSampler::new(vec![coin, die]);
+
+        let n = 40_000;
+        let samples = gibbs.sample(&mut rng, n, 100);
+        assert_eq!(samples.len(), n);
+        assert!(samples.iter().all(|s| s.len() == 2));
+
+        let heads = samples.iter().filter(|s| s[0] == 1).count() as f64 / n as f64;
+        let threes = samples.iter().filter(|s| s[1] == 3).count() as f64 / n as f64;
+        let both = samples.iter().filter(|s| s[0] == 1 && s[1] == 3).count() as f64 / n as f64;
+        assert!((heads - 0.7).abs() < 0.01);
+        assert!((threes - 0.5).abs() < 0.01);
+        assert!((both - 0.35).abs() < 0.01);
+    }
+
+    #[test]
+    fn parallel_tempering_check() {
+        let mut rng = StdRng::seed_from_u64(187);
+        // two modes at 2 and 18 separated by a valley of probability about 1e-14
+        let law: Vec<f64> = (0..21)
+            .map(|i| (-((i as f64 - 2.0).powi(2)) / 2.0).exp() + (-((i as f64 - 18.0).powi(2)) / 2.0).exp())
+            .collect();
+        let bimodal = DiscreteFiniteRandomExperiment::new((0..21).collect::<Vec<usize>>(), &law);
+        let upper_fraction = |samples: &[usize]| samples.iter().filter(|i| **i > 10).count() as f64 / samples.len() as f64;
+
+        let single = bimodal.parallel_tempering(&mut rng, &[1.0], 50_000, 10);
+        assert_eq!(single.len(), 50_000);
+        assert!((upper_fraction(&single) - 0.5).abs() > 0.45);
+
+        let tempered = bimodal.parallel_tempering(&mut rng, &[30.0, 1.0, 10.0, 3.0], 50_000, 10);
+        assert!((upper_fraction(&tempered) - 0.5).abs() < 0.1);
+        let mean = tempered.iter().sum::<usize>() as f64 / 50_000.0;
+        assert!((mean - 10.0).abs() < 2.0);
+    }
+}
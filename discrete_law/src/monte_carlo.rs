@@ -0,0 +1,150 @@
+//! Monte Carlo integration and sampling.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::statistics::mean_and_variance;
+use crate::{DiscreteFiniteRandomExperiment, StatError};
+
+/// Estimate `int_a^b f(x) dx` by `(b - a) / n * sum f(x_i)` with `x_i` uniform on `[a, b]`.
+/// Return the estimate and its standard error.
+pub fn monte_carlo_integrate<R: Rng>(rng: &mut R, a: f64, b: f64, f: impl Fn(f64) -> f64, n: usize) -> (f64, f64) {
+    let values: Vec<f64> = (0..n).map(|_| (b - a) * f(a + (b - a) * rng.random::<f64>())).collect();
+    let (mean, variance) = mean_and_variance(&values);
+    (mean, (variance / n as f64).sqrt())
+}
+
+/// Discrete integration with the experiment as importance law.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Estimate `sum_{x in omega} f(x)` by the mean of `f(X) / P(X)` over `n` draws.
+    pub fn importance_sampling_integrate<R: Rng>(&self, rng: &mut R, f: impl Fn(&T) -> f64, n: usize) -> f64 {
+        let law = self.distribution.probabilities();
+        (0..n)
+            .map(|_| {
+                let i = self.distribution.sample(rng);
+                f(&self.omega[i]) / law[i]
+            })
+            .sum::<f64>() / n as f64
+    }
+}
+
+/// Estimate the permanent `sum_sigma prod_i matrix[i][sigma(i)]` of a square matrix by
+/// `n! * prod_i matrix[i][sigma(i)]` averaged over `n` uniform permutations `sigma`.
+/// Return the estimate and its standard error.
+pub fn mc_permanent_estimate<R: Rng>(matrix: &[Vec<f64>], rng: &mut R, n: usize) -> (f64, f64) {
+    let size = matrix.len();
+    assert!(matrix.iter().all(|row| row.len() == size), "the matrix must be square");
+    let n_permutations: f64 = (1..=size).map(|k| k as f64).product();
+    let columns = DiscreteFiniteRandomExperiment::new((0..size).collect::<Vec<usize>>(), &vec![1.0; size]);
+    let values: Vec<f64> = (0..n)
+        .map(|_| {
+            let sigma = columns.sample_without_replacement(rng, size);
+            n_permutations * sigma.iter().enumerate().map(|(i, j)| matrix[i][*j]).product::<f64>()
+        })
+        .collect();
+    let (mean, variance) = mean_and_variance(&values);
+    (mean, (variance / n as f64).sqrt())
+}
+
+/// Rejection sampling with the experiment as proposal law.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Draw `n_samples` outcome indices from the law proportional to `target_unnormalized`: a proposal `i`
+    /// is accepted with probability `target(i) / (bound * q(i))`. Return the samples and the acceptance rate,
+    /// close to `sum target / bound`.
+    ///
+    /// Fails when `bound` isn't positive and finite, when the target is negative, infinite or NaN at some outcome
+    /// or zero on all of them, and when `target(i) > bound * q(i)` for some outcome.
+    pub fn rejection_sample<R: Rng, Target: Fn(usize) -> f64>(&self, rng: &mut R, target_unnormalized: Target, bound: f64, n_samples: usize) -> Result<(Vec<usize>, f64), StatError> {
+        if !(bound.is_finite() && bound > 0.0) {
+            return Err(StatError::InvalidBound(bound));
+        }
+        let q = self.distribution.probabilities();
+        let target: Vec<f64> = (0..q.len()).map(target_unnormalized).collect();
+        if let Some(i) = (0..q.len()).find(|i| !target[*i].is_finite() || target[*i] < 0.0) {
+            return Err(StatError::InvalidTarget(i));
+        }
+        if target.iter().all(|t| *t == 0.0) {
+            return Err(StatError::ZeroTarget);
+        }
+        if let Some(i) = (0..q.len()).find(|i| target[*i] > bound * q[*i]) {
+            return Err(StatError::UnboundedTarget(i));
+        }
+        let mut samples = Vec::with_capacity(n_samples);
+        let mut proposals = 0;
+        while samples.len() < n_samples {
+            let i = self.distribution.sample(rng);
+            proposals += 1;
+            if rng.random::<f64>() * bound * q[i] < target[i] {
+                samples.push(i);
+            }
+        }
+        let acceptance_rate = if proposals == 0 { 1.0 } else { n_samples as f64 / proposals as f64 };
+        Ok((samples, acceptance_rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn integrate_check() {
+        let mut rng = StdRng::seed_from_u64(151);
+        let (estimate, error) = monte_carlo_integrate(&mut rng, 0.0, 1.0, |x| x * x, 10_000);
+        assert!((estimate - 1.0 / 3.0).abs() < 0.01);
+        // standard deviation of x^2 is 2 / sqrt(45)
+        assert!((error - 2.0 / 45f64.sqrt() / 100.0).abs() < 3e-4);
+
+        // sum of k^2 for k = 0..=10 is 385, a proposal proportional to k + 1 beats the uniform one
+        let omega: Vec<u32> = (0..=10).collect();
+        let square = |k: &u32| (k * k) as f64;
+        let uniform = DiscreteFiniteRandomExperiment::new(omega.clone(), &[1.0; 11]);
+        let weights: Vec<f64> = omega.iter().map(|k| (k + 1) as f64).collect();
+        let tilted = DiscreteFiniteRandomExperiment::new(omega, &weights);
+        let error = |exp: &DiscreteFiniteRandomExperiment<u32>, rng: &mut StdRng| {
+            (0..200).map(|_| (exp.importance_sampling_integrate(rng, square, 100) - 385.0).powi(2)).sum::<f64>()
+        };
+        assert!((tilted.importance_sampling_integrate(&mut rng, square, 100_000) / 385.0 - 1.0).abs() < 0.01);
+        assert!(error(&tilted, &mut rng) < error(&uniform, &mut rng));
+    }
+
+    #[test]
+    fn permanent_check() {
+        let mut rng = StdRng::seed_from_u64(184);
+        let ones = vec![vec![1.0; 3]; 3];
+        let (estimate, error) = mc_permanent_estimate(&ones, &mut rng, 10_000);
+        assert!((estimate - 6.0).abs() <= error + 1e-12);
+
+        // permanent of [[1, 2, 0], [3, 1, 1], [0, 2, 4]] expanded along the first row
+        let matrix = vec![vec![1.0, 2.0, 0.0], vec![3.0, 1.0, 1.0], vec![0.0, 2.0, 4.0]];
+        let exact = (1.0 * 4.0 + 1.0 * 2.0) + 2.0 * (3.0 * 4.0 + 1.0 * 0.0);
+        let (estimate, error) = mc_permanent_estimate(&matrix, &mut rng, 10_000);
+        assert!(error > 0.0);
+        assert!((estimate - exact).abs() < 4.0 * error);
+    }
+
+    #[test]
+    fn rejection_sampling_check() {
+        let mut rng = StdRng::seed_from_u64(186);
+        let proposal = DiscreteFiniteRandomExperiment::new(vec!['a', 'b', 'c', 'd'], &[0.25; 4]);
+        // target proportional to 1, 2, 3, 4: sum 10, largest ratio 4 / 0.25 = 16
+        let target = |i: usize| (i + 1) as f64;
+        let (samples, acceptance_rate) = proposal.rejection_sample(&mut rng, target, 16.0, 100_000).unwrap();
+        assert_eq!(samples.len(), 100_000);
+        assert!((acceptance_rate - 10.0 / 16.0).abs() < 0.01);
+        for i in 0..4 {
+            let frequency = samples.iter().filter(|s| **s == i).count() as f64 / 100_000.0;
+            assert!((frequency - target(i) / 10.0).abs() < 0.01);
+        }
+
+        assert_eq!(proposal.rejection_sample(&mut rng, target, 10.0, 10).unwrap_err(), StatError::UnboundedTarget(2));
+        assert_eq!(proposal.rejection_sample(&mut rng, |_| 0.0, 10.0, 10).unwrap_err(), StatError::ZeroTarget);
+        assert_eq!(proposal.rejection_sample(&mut rng, |i| if i == 1 { f64::NAN } else { 1.0 }, 10.0, 10).unwrap_err(),
+                   StatError::InvalidTarget(1));
+        assert_eq!(proposal.rejection_sample(&mut rng, target, 0.0, 10).unwrap_err(), StatError::InvalidBound(0.0));
+        assert_eq!(proposal.rejection_sample(&mut rng, target, f64::INFINITY, 10).unwrap_err(),
+                   StatError::InvalidBound(f64::INFINITY));
+    }
+}
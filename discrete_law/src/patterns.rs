@@ -0,0 +1,111 @@
+//! Patterns in sequences of i.i.d. draws.
+
+use rand::Rng;
+use rand::distr::Distribution;
+use std::collections::BTreeMap;
+
+use crate::statistics::mean_and_variance;
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Result of `simulate_waiting_for_pattern`.
+#[derive(Debug, Clone)]
+pub struct WaitingPatternResult {
+    /// Mean number of draws until the pattern is completed (runs reaching `max_trials` excluded).
+    pub mean_waiting_time: f64,
+    pub variance: f64,
+    /// Number of runs for each waiting time.
+    pub distribution: BTreeMap<usize, usize>,
+    /// Number of runs where the pattern didn't appear within `max_trials` draws.
+    pub not_found: usize,
+}
+
+/// KMP failure function: `failure[k]` is the length of the longest proper border of `pattern[..=k]`.
+fn failure_function<T: PartialEq>(pattern: &[T]) -> Vec<usize> {
+    let mut failure = vec![0; pattern.len()];
+    let mut k = 0;
+    for i in 1..pattern.len() {
+        while k > 0 && pattern[i] != pattern[k] {
+            k = failure[k - 1];
+        }
+        if pattern[i] == pattern[k] {
+            k += 1;
+        }
+        failure[i] = k;
+    }
+    failure
+}
+
+/// Waiting time for a pattern in i.i.d. draws of `self`.
+impl<T: PartialEq + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Draw until `pattern` appears as consecutive outcomes, `repetitions` times.
+    /// The matched prefix length is tracked with the KMP automaton.
+    pub fn simulate_waiting_for_pattern<R: Rng>(&self, rng: &mut R, pattern: &[T], max_trials: usize, repetitions: usize) -> WaitingPatternResult {
+        assert!(!pattern.is_empty(), "pattern must not be empty");
+        let failure = failure_function(pattern);
+        let mut distribution = BTreeMap::new();
+        let mut not_found = 0;
+
+        for _ in 0..repetitions {
+            let mut state = 0;
+            let found = (1..=max_trials).find(|_| {
+                let x = self.sample(rng);
+                while state > 0 && x != pattern[state] {
+                    state = failure[state - 1];
+                }
+                if x == pattern[state] {
+                    state += 1;
+                }
+                state == pattern.len()
+            });
+            match found {
+                Some(t) => *distribution.entry(t).or_insert(0) += 1,
+                None => not_found += 1,
+            }
+        }
+
+        let times: Vec<f64> = distribution.iter()
+            .flat_map(|(t, c)| std::iter::repeat_n(*t as f64, *c))
+            .collect();
+        let (mean, variance) = mean_and_variance(&times);
+        WaitingPatternResult { mean_waiting_time: mean, variance, distribution, not_found }
+    }
+
+    /// Expected waiting time by Conway's leading numbers:
+    /// sum over the lengths `k` where the prefix and suffix of length `k` agree of `1 / P(prefix)`.
+    pub fn theoretical_waiting_time(&self, pattern: &[T]) -> f64 {
+        let law = self.distribution.probabilities();
+        let p = |x: &T| self.omega.iter().position(|o| o == x).map_or(0.0, |i| law[i]);
+        (1..=pattern.len())
+            .filter(|k| pattern[..*k] == pattern[pattern.len() - k..])
+            .map(|k| 1.0 / pattern[..k].iter().map(p).product::<f64>())
+            .sum()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn waiting_for_pattern_check() {
+        let mut rng = StdRng::seed_from_u64(128);
+        let coin = DiscreteFiniteRandomExperiment::new(vec!['H', 'T'], &[0.5, 0.5]);
+
+        assert!((coin.theoretical_waiting_time(&['H', 'H', 'H']) - 14.0).abs() < 1e-12);
+        assert!((coin.theoretical_waiting_time(&['H', 'H', 'T']) - 8.0).abs() < 1e-12);
+        assert!((coin.theoretical_waiting_time(&['H', 'T', 'H']) - 10.0).abs() < 1e-12);
+
+        let res = coin.simulate_waiting_for_pattern(&mut rng, &['H', 'H', 'H'], 10_000, 20_000);
+        assert_eq!(res.not_found, 0);
+        assert_eq!(*res.distribution.keys().next().unwrap(), 3);
+        assert!((res.mean_waiting_time - 14.0).abs() < 0.3);
+        // Var = 142 for HHH
+        assert!((res.variance / 142.0 - 1.0).abs() < 0.1);
+
+        let res = coin.simulate_waiting_for_pattern(&mut rng, &['H', 'T', 'H'], 10_000, 20_000);
+        assert!((res.mean_waiting_time - 10.0).abs() < 0.3);
+    }
+}
@@ -0,0 +1,984 @@
+//! Stochastic processes driven by an experiment.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::statistics::mean_and_variance;
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Population above which a branching process is no longer simulated, its extinction
+/// probability being computed exactly instead.
+const BRANCHING_POPULATION_CAP: usize = 200;
+
+/// Galton-Watson branching process, `self` is the offspring distribution.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Probability generating function `G(s) = sum p_k s^k`.
+    pub fn generating_function(&self, s: f64) -> f64 {
+        self.omega.iter()
+            .zip(self.distribution.probabilities())
+            .map(|(k, p)| p * s.powi(*k as i32))
+            .sum()
+    }
+
+    /// Estimate the probability that the process started from one individual
+    /// dies out within `max_generations` generations.
+    ///
+    /// A population of `n` individuals exceeding 200 with `g` generations left is not simulated further:
+    /// its `n` independent lines all die out within `g` generations with probability `G_g(0)^n`,
+    /// `G_g` being the `g`-th iterate of the generating function, which is added to the count of extinctions.
+    pub fn extinction_probability_simulation<R: Rng>(&self, rng: &mut R, max_generations: usize, repetitions: usize) -> f64 {
+        // extinct_within[g] = G_g(0), probability that one individual has no descendant after g generations
+        let mut extinct_within = vec![0.0; max_generations + 1];
+        for g in 1..=max_generations {
+            extinct_within[g] = self.generating_function(extinct_within[g - 1]);
+        }
+
+        let mut extinct = 0.0;
+        for _ in 0..repetitions {
+            let mut population: usize = 1;
+            for generation in 1..=max_generations {
+                population = (0..population).map(|_| self.sample(rng)).sum();
+                if population == 0 {
+                    extinct += 1.0;
+                    break;
+                }
+                if population > BRANCHING_POPULATION_CAP {
+                    extinct += extinct_within[max_generations - generation].powi(population as i32);
+                    break;
+                }
+            }
+        }
+        extinct / repetitions as f64
+    }
+
+    /// Smallest fixed point of `q = G(q)` in [0, 1]: 1 when the mean offspring is at most 1
+    /// (unless each individual has exactly one child), else found by iterating `G` from 0.
+    pub fn theoretical_extinction_probability(&self) -> f64 {
+        let probabilities = self.distribution.probabilities();
+        let mean: f64 = self.omega.iter().zip(&probabilities).map(|(k, p)| *k as f64 * p).sum();
+        if mean <= 1.0 {
+            let single_child: f64 = self.omega.iter().zip(&probabilities).filter(|(k, _)| **k == 1).map(|(_, p)| p).sum();
+            return if single_child >= 1.0 { 0.0 } else { 1.0 };
+        }
+        let mut q = 0.0;
+        for _ in 0..1_000_000 {
+            let next = self.generating_function(q);
+            if (next - q).abs() < 1e-12 {
+                return next;
+            }
+            q = next;
+        }
+        q
+    }
+}
+
+
+/// Statistics of `simulate_random_walk_stats`.
+#[derive(Debug, Clone)]
+pub struct RandomWalkStats {
+    pub mean_final_position: f64,
+    pub variance_final_position: f64,
+    /// Mean first return time to 0 among the walks returning within `n_steps`
+    /// (infinite if none returns).
+    pub expected_return_time: f64,
+}
+
+/// Simple random walk on the integers, `true` is a +1 step and `false` a -1 step.
+impl DiscreteFiniteRandomExperiment<bool> {
+    /// Trajectory of the walk started at 0: positions at steps `0..=n_steps`.
+    pub fn simulate_random_walk<R: Rng>(&self, rng: &mut R, n_steps: usize) -> Vec<i64> {
+        let mut position = 0;
+        let mut path = Vec::with_capacity(n_steps + 1);
+        path.push(position);
+        for _ in 0..n_steps {
+            position += if self.sample(rng) { 1 } else { -1 };
+            path.push(position);
+        }
+        path
+    }
+
+    /// Run `repetitions` walks of `n_steps` steps and estimate final position moments
+    /// and the return time to 0.
+    pub fn simulate_random_walk_stats<R: Rng>(&self, rng: &mut R, n_steps: usize, repetitions: usize) -> RandomWalkStats {
+        let mut finals = Vec::with_capacity(repetitions);
+        let mut return_times = Vec::new();
+        for _ in 0..repetitions {
+            let path = self.simulate_random_walk(rng, n_steps);
+            finals.push(path[n_steps] as f64);
+            if let Some(t) = path.iter().skip(1).position(|x| *x == 0) {
+                return_times.push((t + 1) as f64);
+            }
+        }
+
+        let (mean, variance) = mean_and_variance(&finals);
+        let expected_return_time = if return_times.is_empty() {
+            f64::INFINITY
+        } else {
+            return_times.iter().sum::<f64>() / return_times.len() as f64
+        };
+        RandomWalkStats {
+            mean_final_position: mean,
+            variance_final_position: variance,
+            expected_return_time,
+        }
+    }
+}
+
+/// Pólya urn, the law weights of `self` are the initial number of balls of each color.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    fn initial_urn(&self) -> Vec<usize> {
+        self.distribution.law.iter()
+            .map(|w| {
+                assert!(w.fract() == 0.0 && *w >= 0.0, "urn weights must be ball counts");
+                *w as usize
+            })
+            .collect()
+    }
+
+    /// Draw `n_draws` balls, each drawn ball is put back with one more ball of its color.
+    /// Return the drawn colors and the final urn composition.
+    pub fn polya_urn_simulation<R: Rng>(&self, rng: &mut R, n_draws: usize) -> (Vec<T>, Vec<usize>) {
+        let mut urn = self.initial_urn();
+        let mut draws = Vec::with_capacity(n_draws);
+        for _ in 0..n_draws {
+            let mut ball = rng.random_range(0..urn.iter().sum::<usize>());
+            let color = urn.iter().position(|c| {
+                if ball < *c { true } else { ball -= c; false }
+            }).unwrap();
+            urn[color] += 1;
+            draws.push(self.omega[color].clone());
+        }
+        (draws, urn)
+    }
+
+    /// Experiment whose law is the mean proportion of each color after `n_draws`, over `repetitions`
+    /// urns: the expectation of the limiting Dirichlet law, whose parameters are the initial ball counts.
+    pub fn polya_urn_limiting_distribution<R: Rng>(&self, rng: &mut R, n_draws: usize, repetitions: usize) -> DiscreteFiniteRandomExperiment<T> {
+        assert!(repetitions > 0, "at least one repetition is required");
+        let mut proportions = vec![0.0; self.omega.len()];
+        for _ in 0..repetitions {
+            let (_, urn) = self.polya_urn_simulation(rng, n_draws);
+            let total = urn.iter().sum::<usize>() as f64;
+            proportions.iter_mut().zip(&urn).for_each(|(p, c)| *p += *c as f64 / total);
+        }
+        DiscreteFiniteRandomExperiment::new(self.omega.clone(), &proportions)
+    }
+}
+
+/// Result of `simulate_discrete_queue`.
+#[derive(Debug, Clone)]
+pub struct QueueSimulationResult {
+    /// Mean number of customers in the system at the end of a step.
+    pub mean_queue_length: f64,
+    /// Fraction of steps where the server is busy.
+    pub utilization: f64,
+    /// Fraction of steps ending with an empty system.
+    pub proportion_empty: f64,
+    /// M/M/1 mean number in system `rho/(1-rho)` with `rho = arrival_prob / service_prob`
+    /// (infinite when `rho >= 1`).
+    pub theoretical_mean_queue_length: f64,
+}
+
+/// Discrete time single server queue, `self` tells whether the service
+/// in progress completes during a step (geometric service time).
+impl DiscreteFiniteRandomExperiment<bool> {
+    /// At each step a customer arrives with probability `arrival_prob`,
+    /// then the customer in service (if any) leaves when `self` draws `true`.
+    pub fn simulate_discrete_queue<R: Rng>(&self, arrival_prob: f64, rng: &mut R, n_steps: usize) -> QueueSimulationResult {
+        let mut length: usize = 0;
+        let mut total_length = 0;
+        let mut busy = 0;
+        let mut empty = 0;
+        for _ in 0..n_steps {
+            if rng.random::<f64>() < arrival_prob {
+                length += 1;
+            }
+            if length > 0 {
+                busy += 1;
+                if self.sample(rng) {
+                    length -= 1;
+                }
+            }
+            total_length += length;
+            if length == 0 {
+                empty += 1;
+            }
+        }
+
+        let rho = arrival_prob / self.probability_of_true();
+        QueueSimulationResult {
+            mean_queue_length: total_length as f64 / n_steps as f64,
+            utilization: busy as f64 / n_steps as f64,
+            proportion_empty: empty as f64 / n_steps as f64,
+            theoretical_mean_queue_length: if rho < 1.0 { rho / (1.0 - rho) } else { f64::INFINITY },
+        }
+    }
+}
+
+/// Result of `simulate_multi_server_queue`.
+#[derive(Debug, Clone)]
+pub struct QueueResult {
+    /// Mean time between arrival and start of service.
+    pub mean_waiting_time: f64,
+    /// Time average number of waiting customers, by Little's law `arrival_rate * mean_waiting_time`.
+    pub mean_queue_length: f64,
+    /// Fraction of the time until the last departure that each server is busy.
+    pub server_utilization: Vec<f64>,
+}
+
+/// Multi-server first come first served queue with Poisson arrivals, `self` is the service time law (M/G/k queue).
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Serve `n_customers` arriving at rate `arrival_rate`, each taken by the first server to become free.
+    pub fn simulate_multi_server_queue<R: Rng>(&self, rng: &mut R, n_servers: usize, arrival_rate: f64, n_customers: usize) -> QueueResult {
+        let mut free_at = vec![0.0f64; n_servers];
+        let mut busy = vec![0.0; n_servers];
+        let mut arrival = 0.0;
+        let mut total_wait = 0.0;
+        for _ in 0..n_customers {
+            arrival += -(1.0 - rng.random::<f64>()).ln() / arrival_rate;
+            let server = (0..n_servers).min_by(|a, b| free_at[*a].total_cmp(&free_at[*b])).unwrap();
+            let start = free_at[server].max(arrival);
+            let service: f64 = self.sample(rng).into();
+            total_wait += start - arrival;
+            busy[server] += service;
+            free_at[server] = start + service;
+        }
+        let end = free_at.iter().copied().fold(arrival, f64::max);
+        let mean_waiting_time = total_wait / n_customers as f64;
+        QueueResult {
+            mean_waiting_time,
+            mean_queue_length: arrival_rate * mean_waiting_time,
+            server_utilization: busy.iter().map(|b| b / end).collect(),
+        }
+    }
+}
+
+/// Erlang C mean waiting time of an M/M/k queue with `n_servers` servers of rate `service_rate`
+/// (infinite when `arrival_rate >= n_servers * service_rate`).
+pub fn erlang_c_waiting_time(n_servers: usize, arrival_rate: f64, service_rate: f64) -> f64 {
+    let k = n_servers as f64;
+    let a = arrival_rate / service_rate;
+    if a >= k {
+        return f64::INFINITY;
+    }
+    // terms a^i / i! for i = 0..k
+    let terms: Vec<f64> = (0..=n_servers).scan(1.0, |t, i| {
+        let term = *t;
+        *t *= a / (i + 1) as f64;
+        Some(term)
+    }).collect();
+    let queued = terms[n_servers] / (1.0 - a / k);
+    let probability_of_waiting = queued / (terms[..n_servers].iter().sum::<f64>() + queued);
+    probability_of_waiting / (k * service_rate - arrival_rate)
+}
+
+/// Health state of an individual in the SIR model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Health {
+    Susceptible,
+    Infected,
+    Recovered,
+}
+
+/// Trajectory of `simulate_sir`.
+#[derive(Debug, Clone)]
+pub struct SirTrajectory {
+    /// (S, I, R) counts at steps `0..=n_steps`.
+    pub counts: Vec<(usize, usize, usize)>,
+    /// Basic reproduction number `beta * mean_contacts / gamma`.
+    pub r0: f64,
+}
+
+impl SirTrajectory {
+    /// Whether the epidemic is predicted to grow (`R0 > 1`).
+    pub fn predicted_growth(&self) -> bool {
+        self.r0 > 1.0
+    }
+}
+
+/// Discrete time SIR epidemic, `self` is the number of contacts of an infected individual per step.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Each step, every infected individual meets a number of uniformly chosen individuals
+    /// drawn from `self` and infects each susceptible one with probability `beta`,
+    /// then recovers with probability `gamma`.
+    pub fn simulate_sir<R: Rng>(&self, rng: &mut R, n_individuals: usize, beta: f64, gamma: f64, initial_infected: usize, n_steps: usize) -> SirTrajectory {
+        assert!(initial_infected <= n_individuals, "more infected than individuals");
+        let mut health = vec![Health::Susceptible; n_individuals];
+        health[..initial_infected].fill(Health::Infected);
+
+        let count = |health: &[Health]| {
+            let n = |h| health.iter().filter(|x| **x == h).count();
+            (n(Health::Susceptible), n(Health::Infected), n(Health::Recovered))
+        };
+
+        let mut counts = vec![count(&health)];
+        for _ in 0..n_steps {
+            let infected: Vec<usize> = (0..n_individuals).filter(|i| health[*i] == Health::Infected).collect();
+            for i in &infected {
+                for _ in 0..self.sample(rng) {
+                    let contact = rng.random_range(0..n_individuals);
+                    if health[contact] == Health::Susceptible && rng.random::<f64>() < beta {
+                        health[contact] = Health::Infected;
+                    }
+                }
+                if rng.random::<f64>() < gamma {
+                    health[*i] = Health::Recovered;
+                }
+            }
+            counts.push(count(&health));
+        }
+
+        let mean_contacts: f64 = self.omega.iter()
+            .zip(self.distribution.probabilities())
+            .map(|(k, p)| *k as f64 * p)
+            .sum();
+        SirTrajectory { counts, r0: beta * mean_contacts / gamma }
+    }
+}
+
+/// Result of `simulate_portfolio`.
+#[derive(Debug, Clone)]
+pub struct PortfolioResult {
+    pub mean_final_value: f64,
+    pub variance: f64,
+    /// Fraction of paths ending below the initial value.
+    pub probability_of_loss: f64,
+    /// Sorted final values of the simulated paths.
+    pub final_values: Vec<f64>,
+}
+
+impl PortfolioResult {
+    /// Empirical `alpha`-quantile of the final value.
+    pub fn value_at_risk(&self, alpha: f64) -> f64 {
+        assert!((0.0..=1.0).contains(&alpha), "alpha must be in [0, 1]");
+        let i = ((alpha * self.final_values.len() as f64).ceil() as usize).max(1) - 1;
+        self.final_values[i.min(self.final_values.len() - 1)]
+    }
+}
+
+/// Portfolio simulation, each period the value is multiplied by a draw of `self`.
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    pub fn simulate_portfolio<R: Rng>(&self, rng: &mut R, initial_value: f64, n_periods: usize, repetitions: usize) -> PortfolioResult {
+        let mut final_values: Vec<f64> = (0..repetitions)
+            .map(|_| (0..n_periods).fold(initial_value, |v, _| v * self.sample(rng).into()))
+            .collect();
+        final_values.sort_by(|a, b| a.total_cmp(b));
+
+        let (mean, variance) = mean_and_variance(&final_values);
+        PortfolioResult {
+            mean_final_value: mean,
+            variance,
+            probability_of_loss: final_values.iter().filter(|v| **v < initial_value).count() as f64 / repetitions as f64,
+            final_values,
+        }
+    }
+}
+
+/// Result of `simulate_voter_model`.
+#[derive(Debug, Clone)]
+pub struct ConsensusResult {
+    /// Number of agents holding each opinion (indexed like `omega`) at each step, start included.
+    pub opinion_counts: Vec<Vec<usize>>,
+    /// Step where all agents agree, if reached.
+    pub steps_to_consensus: Option<usize>,
+    /// Index of the opinion reaching consensus.
+    pub consensus_opinion: Option<usize>,
+}
+
+/// Voter model on the complete graph, initial opinions are i.i.d. draws of `self`.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// At each step a random agent copies the opinion of another random agent.
+    /// The simulation stops at consensus or after `n_steps` steps.
+    pub fn simulate_voter_model<R: Rng>(&self, rng: &mut R, n_agents: usize, n_steps: usize) -> ConsensusResult {
+        assert!(n_agents > 1, "at least two agents are required");
+        let mut opinions: Vec<usize> = (0..n_agents).map(|_| self.distribution.sample(rng)).collect();
+        let mut counts = vec![0; self.omega.len()];
+        opinions.iter().for_each(|o| counts[*o] += 1);
+
+        let consensus = |counts: &[usize]| counts.iter().position(|c| *c == n_agents);
+        let mut opinion_counts = vec![counts.clone()];
+        let mut steps_to_consensus = consensus(&counts).map(|_| 0);
+
+        for t in 1..=n_steps {
+            if steps_to_consensus.is_some() {
+                break;
+            }
+            let agent = rng.random_range(0..n_agents);
+            let mut neighbor = rng.random_range(0..n_agents - 1);
+            if neighbor >= agent {
+                neighbor += 1;
+            }
+            counts[opinions[agent]] -= 1;
+            opinions[agent] = opinions[neighbor];
+            counts[opinions[agent]] += 1;
+            opinion_counts.push(counts.clone());
+            if consensus(&counts).is_some() {
+                steps_to_consensus = Some(t);
+            }
+        }
+
+        ConsensusResult {
+            consensus_opinion: consensus(&counts),
+            opinion_counts,
+            steps_to_consensus,
+        }
+    }
+}
+
+/// Partial sums `S_n = X_1 + ... + X_n` of i.i.d. draws of `self`.
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Trajectory `[S_1, ..., S_n]`.
+    pub fn cumulative_sum_process<R: Rng>(&self, rng: &mut R, n: usize) -> Vec<f64> {
+        let mut sum = 0.0;
+        (0..n).map(|_| {
+            sum += self.sample(rng).into();
+            sum
+        }).collect()
+    }
+
+    /// Mean and variance of `S_t` at each time `t` over `repetitions` trajectories.
+    pub fn cumulative_sum_stats<R: Rng>(&self, rng: &mut R, n: usize, repetitions: usize) -> (Vec<f64>, Vec<f64>) {
+        let mut sums = vec![0.0; n];
+        let mut squares = vec![0.0; n];
+        for _ in 0..repetitions {
+            for (t, x) in self.cumulative_sum_process(rng, n).into_iter().enumerate() {
+                sums[t] += x;
+                squares[t] += x * x;
+            }
+        }
+        let r = repetitions as f64;
+        let means: Vec<f64> = sums.iter().map(|s| s / r).collect();
+        let variances = squares.iter().zip(&means)
+            .map(|(sq, m)| (sq - r * m * m) / (r - 1.0))
+            .collect();
+        (means, variances)
+    }
+}
+
+/// Maximum position of each generation of a branching random walk
+/// (negative infinity once the population has died out).
+pub fn frontier(generations: &[Vec<f64>]) -> Vec<f64> {
+    generations.iter()
+        .map(|g| g.iter().cloned().fold(f64::NEG_INFINITY, f64::max))
+        .collect()
+}
+
+/// Branching random walk, `self` is the law of the ancestor position.
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Each particle has a number of children drawn from `offspring_experiment`,
+    /// each child is displaced from its parent by a draw of `displacement_experiment`.
+    /// Return the particle positions of generations `0..=n_generations`.
+    pub fn simulate_branching_random_walk<R: Rng>(&self, offspring_experiment: &DiscreteFiniteRandomExperiment<usize>, displacement_experiment: &Self, rng: &mut R, n_generations: usize) -> Vec<Vec<f64>> {
+        let mut generations = vec![vec![self.sample(rng).into()]];
+        for g in 0..n_generations {
+            let children = generations[g].iter()
+                .flat_map(|x| {
+                    let k = offspring_experiment.sample(rng);
+                    (0..k).map(|_| x + displacement_experiment.sample(rng).into()).collect::<Vec<f64>>()
+                })
+                .collect();
+            generations.push(children);
+        }
+        generations
+    }
+}
+
+/// Result of `simulate_renewal_reward`.
+#[derive(Debug, Clone)]
+pub struct RenewalRewardResult {
+    /// Total reward earned up to `max_time` divided by the time simulated.
+    pub estimated_long_run_reward_rate: f64,
+    /// Renewal reward theorem rate `E[reward] / E[inter-renewal time]`.
+    pub theoretical_rate: f64,
+}
+
+/// Renewal reward process, each cycle is a draw `x` of `self`
+/// lasting `inter_renewal_time(x)` and earning `reward_at_renewal(x)` at its end.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Panics unless `max_time` is positive and finite, `repetitions` positive and the inter-renewal time
+    /// of every outcome of positive probability positive.
+    pub fn simulate_renewal_reward<R: Rng, V: Fn(&T) -> f64, I: Fn(&T) -> f64>(&self, rng: &mut R, inter_renewal_time: I, reward_at_renewal: V, max_time: f64, repetitions: usize) -> RenewalRewardResult {
+        assert!(max_time > 0.0 && max_time.is_finite(), "max_time must be positive and finite");
+        assert!(repetitions > 0, "at least one repetition is required");
+        let p = self.distribution.probabilities();
+        assert!(self.omega.iter().zip(&p).all(|(x, px)| *px == 0.0 || inter_renewal_time(x) > 0.0),
+                "inter-renewal times must be positive");
+        let mut reward = 0.0;
+        for _ in 0..repetitions {
+            let mut time = 0.0;
+            loop {
+                let x = &self.omega[self.distribution.sample(rng)];
+                time += inter_renewal_time(x);
+                if time > max_time {
+                    break;
+                }
+                reward += reward_at_renewal(x);
+            }
+        }
+
+        let mean = |f: &dyn Fn(&T) -> f64| self.omega.iter().zip(&p).map(|(x, px)| f(x) * px).sum::<f64>();
+        RenewalRewardResult {
+            estimated_long_run_reward_rate: reward / (max_time * repetitions as f64),
+            theoretical_rate: mean(&reward_at_renewal) / mean(&inter_renewal_time),
+        }
+    }
+}
+
+/// Compound Poisson process with geometric approximation of the jump times,
+/// `self` tells whether a jump happens during a time step.
+impl DiscreteFiniteRandomExperiment<bool> {
+    /// Jumps of rate `lambda` up to `time_horizon`: time is cut in steps of length
+    /// `dt = P(true) / lambda`, a jump happening at the end of a step when `self` draws `true`,
+    /// with a size drawn from `jump_distribution`.
+    /// Return the `(time, cumulative_sum)` pairs at each jump.
+    ///
+    /// Panics unless `lambda` and `P(true)` are positive and `time_horizon` is finite and non negative.
+    pub fn simulate_compound_poisson<R: Rng>(&self, jump_distribution: &DiscreteFiniteRandomExperiment<f64>, rng: &mut R, lambda: f64, time_horizon: f64) -> Vec<(f64, f64)> {
+        assert!(lambda > 0.0 && lambda.is_finite(), "lambda must be positive and finite");
+        assert!(self.probability_of_true() > 0.0, "the step experiment must give a positive probability to true");
+        assert!(time_horizon >= 0.0 && time_horizon.is_finite(), "time_horizon must be finite and non negative");
+        let dt = self.probability_of_true() / lambda;
+        let n_steps = (time_horizon / dt).floor() as usize;
+        let mut sum = 0.0;
+        let mut jumps = Vec::new();
+        for step in 1..=n_steps {
+            if self.sample(rng) {
+                sum += jump_distribution.sample(rng);
+                jumps.push((step as f64 * dt, sum));
+            }
+        }
+        jumps
+    }
+
+    /// Mean `lambda * t * E[J]` and variance `lambda * t * E[J^2]` of a compound Poisson process at time `t`.
+    pub fn compound_poisson_moments(jump_distribution: &DiscreteFiniteRandomExperiment<f64>, lambda: f64, time: f64) -> (f64, f64) {
+        let mean = jump_distribution.expected_value();
+        let second_moment = jump_distribution.variance() + mean * mean;
+        (lambda * time * mean, lambda * time * second_moment)
+    }
+}
+
+/// Cramér-Lundberg risk model, `self` is the claim size law (non negative claims arriving
+/// as a Poisson process of rate 1) and premiums are collected at `premium_rate` per unit time.
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Lundberg bound `exp(-r * initial_surplus)` on the ruin probability, where the adjustment
+    /// coefficient `r > 0` solves `m(r) = 1 + (1 + theta) * mu * r` with `theta = premium_rate / mu - 1`.
+    /// None when the net profit condition `premium_rate > mu` fails or no claim is positive.
+    pub fn cramer_lundberg_bound(&self, initial_surplus: f64, premium_rate: f64) -> Option<f64> {
+        let claims: Vec<(f64, f64)> = self.omega.iter()
+            .map(|x| x.clone().into())
+            .zip(self.distribution.probabilities())
+            .collect();
+        let mu = self.expected_value();
+        if premium_rate <= mu || !claims.iter().any(|(x, p)| *x > 0.0 && *p > 0.0) {
+            return None;
+        }
+        // h(r) = m(r) - 1 - premium_rate * r is convex, negative right after 0 and goes to infinity
+        let h = |r: f64| claims.iter().map(|(x, p)| p * (r * x).exp()).sum::<f64>() - 1.0 - premium_rate * r;
+        let mut hi = 1.0;
+        while h(hi) < 0.0 {
+            hi *= 2.0;
+        }
+        let mut lo = 0.0;
+        for _ in 0..200 {
+            let mid = 0.5 * (lo + hi);
+            if h(mid) < 0.0 {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some((-hi * initial_surplus).exp())
+    }
+
+    /// Proportion of `repetitions` surplus paths ruined (surplus below 0) within the first `n_claims` claims.
+    pub fn simulate_ruin<R: Rng>(&self, rng: &mut R, initial_surplus: f64, premium_rate: f64, n_claims: usize, repetitions: usize) -> f64 {
+        let ruined = (0..repetitions)
+            .filter(|_| {
+                let mut surplus = initial_surplus;
+                (0..n_claims).any(|_| {
+                    let waiting_time = -(1.0 - rng.random::<f64>()).ln();
+                    surplus += premium_rate * waiting_time - self.sample(rng).into();
+                    surplus < 0.0
+                })
+            })
+            .count();
+        ruined as f64 / repetitions as f64
+    }
+}
+
+/// Geometric law on `1, 2, ...` of success probability `p`, truncated where the tail falls below 1e-12.
+fn geometric(p: f64) -> DiscreteFiniteRandomExperiment<usize> {
+    let cap = ((1e-12f64).ln() / (1.0 - p).ln()).ceil().max(1.0) as usize;
+    let law: Vec<f64> = (0..cap).map(|k| p * (1.0 - p).powi(k as i32)).collect();
+    DiscreteFiniteRandomExperiment::new((1..=cap).collect(), &law)
+}
+
+/// Spatial birth-death process on a `grid_size` x `grid_size` grid in discrete time.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Births happen with probability `birth_rate` per step at a uniform grid site, so the times between
+    /// births are geometric, and each point lives a geometric number of steps of mean `1 / death_rate`.
+    /// Return the number of points at each of the `n_steps` steps.
+    pub fn simulate_spatial_birth_death<R: Rng>(rng: &mut R, grid_size: usize, birth_rate: f64, death_rate: f64, n_steps: usize) -> Vec<usize> {
+        let between_births = geometric(birth_rate);
+        let lifetime = geometric(death_rate);
+        // (site, step of death) of the living points
+        let mut points: Vec<((usize, usize), usize)> = Vec::new();
+        let mut next_birth = between_births.sample(rng) - 1;
+        let mut population = Vec::with_capacity(n_steps);
+        for step in 0..n_steps {
+            points.retain(|(_, death)| *death > step);
+            while next_birth == step {
+                let site = (rng.random_range(0..grid_size), rng.random_range(0..grid_size));
+                points.push((site, step + lifetime.sample(rng)));
+                next_birth += between_births.sample(rng);
+            }
+            population.push(points.len());
+        }
+        population
+    }
+
+    /// Equilibrium law of the population, Poisson(`birth_rate / death_rate`), over `0..n_max`.
+    pub fn birth_death_equilibrium(birth_rate: f64, death_rate: f64, n_max: usize) -> Vec<f64> {
+        let mean = birth_rate / death_rate;
+        (0..n_max)
+            .scan((-mean).exp(), |p, k| {
+                let current = *p;
+                *p *= mean / (k + 1) as f64;
+                Some(current)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::total_variation_distance;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn extinction_check() {
+        let mut rng = StdRng::seed_from_u64(105);
+
+        // mean offspring 0.7
+        let sub = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[0.5, 0.3, 0.2]);
+        assert!((sub.theoretical_extinction_probability() - 1.0).abs() < 1e-9);
+        assert_eq!(sub.extinction_probability_simulation(&mut rng, 200, 2_000), 1.0);
+
+        // mean offspring 1.3, q = 0.4
+        let sup = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[0.2, 0.3, 0.5]);
+        assert!((sup.theoretical_extinction_probability() - 0.4).abs() < 1e-9);
+        let q = sup.extinction_probability_simulation(&mut rng, 200, 2_000);
+        assert!((q - 0.4).abs() < 0.04);
+
+        // critical, populations beyond the cap can still die out within the generations left
+        let critical = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[0.3, 0.4, 0.3]);
+        let exact = (0..100).fold(0.0, |q, _| critical.generating_function(q));
+        let q = critical.extinction_probability_simulation(&mut rng, 100, 4_000);
+        assert!((q - exact).abs() < 0.015);
+        // the fixed point iteration only converges like 1/n at the critical point
+        assert_eq!(critical.theoretical_extinction_probability(), 1.0);
+        let single_child = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2], &[0.0, 1.0, 0.0]);
+        assert_eq!(single_child.theoretical_extinction_probability(), 0.0);
+    }
+
+    #[test]
+    fn random_walk_check() {
+        let mut rng = StdRng::seed_from_u64(109);
+        let coin = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.5, 0.5]);
+
+        let path = coin.simulate_random_walk(&mut rng, 50);
+        assert_eq!(path.len(), 51);
+        assert_eq!(path[0], 0);
+        assert!(path.windows(2).all(|w| (w[1] - w[0]).abs() == 1));
+
+        let stats = coin.simulate_random_walk_stats(&mut rng, 100, 5_000);
+        assert!(stats.mean_final_position.abs() < 0.5);
+        assert!((stats.variance_final_position - 100.0).abs() < 6.0);
+        assert!(stats.expected_return_time >= 2.0);
+    }
+
+    #[test]
+    fn polya_urn_check() {
+        let mut rng = StdRng::seed_from_u64(110);
+        let urn = DiscreteFiniteRandomExperiment::new(vec!['r', 'b'], &[1.0, 1.0]);
+
+        let (draws, composition) = urn.polya_urn_simulation(&mut rng, 100);
+        assert_eq!(draws.len(), 100);
+        assert_eq!(composition.iter().sum::<usize>(), 102);
+        assert_eq!(composition[0] - 1, draws.iter().filter(|c| **c == 'r').count());
+
+        // limiting proportion of red is Beta(1,1): uniform on (0,1)
+        let mut bins = [0usize; 10];
+        for _ in 0..4_000 {
+            let (_, composition) = urn.polya_urn_simulation(&mut rng, 200);
+            let red = composition[0] as f64 / 202.0;
+            bins[((red * 10.0) as usize).min(9)] += 1;
+        }
+        assert!(bins.iter().all(|b| (*b as f64 / 4_000.0 - 0.1).abs() < 0.02));
+
+        let limit = urn.polya_urn_limiting_distribution(&mut rng, 200, 2_000);
+        assert_eq!(limit.omega, vec!['r', 'b']);
+        assert!((limit.distribution.probabilities()[0] - 0.5).abs() < 0.03);
+
+        // with [2, 1] balls the limit is Dirichlet(2, 1), of mean (2/3, 1/3)
+        let skewed = DiscreteFiniteRandomExperiment::new(vec!['r', 'b'], &[2.0, 1.0]);
+        let limit = skewed.polya_urn_limiting_distribution(&mut rng, 200, 2_000);
+        assert!((limit.distribution.probabilities()[0] - 2.0 / 3.0).abs() < 0.03);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one repetition is required")]
+    fn polya_urn_no_repetition() {
+        let mut rng = StdRng::seed_from_u64(110);
+        let urn = DiscreteFiniteRandomExperiment::new(vec!['r', 'b'], &[1.0, 1.0]);
+        urn.polya_urn_limiting_distribution(&mut rng, 10, 0);
+    }
+
+    #[test]
+    fn discrete_queue_check() {
+        let mut rng = StdRng::seed_from_u64(111);
+        let service = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.6, 0.4]);
+        let res = service.simulate_discrete_queue(0.3, &mut rng, 200_000);
+        assert!((res.utilization - 0.5).abs() < 0.01);
+        assert!((res.theoretical_mean_queue_length - 1.0).abs() < 1e-12);
+        assert!(res.mean_queue_length > 0.0 && res.mean_queue_length < 2.0);
+        assert!(res.proportion_empty > 0.4);
+    }
+
+    #[test]
+    fn sir_check() {
+        let mut rng = StdRng::seed_from_u64(112);
+
+        let one_contact = DiscreteFiniteRandomExperiment::new(vec![1], &[1.0]);
+        for _ in 0..20 {
+            let res = one_contact.simulate_sir(&mut rng, 500, 0.1, 0.5, 5, 200);
+            assert!(!res.predicted_growth());
+            assert_eq!(res.counts.last().unwrap().1, 0);
+            assert!(res.counts.iter().all(|(s, i, r)| s + i + r == 500));
+        }
+
+        let contacts = DiscreteFiniteRandomExperiment::new(vec![4, 5, 6], &[1.0, 1.0, 1.0]);
+        let res = contacts.simulate_sir(&mut rng, 1_000, 0.5, 0.2, 5, 50);
+        assert!((res.r0 - 12.5).abs() < 1e-9);
+        assert!(res.predicted_growth());
+        assert!(res.counts[3].1 > res.counts[0].1);
+    }
+
+    #[test]
+    fn portfolio_check() {
+        let mut rng = StdRng::seed_from_u64(113);
+        let returns = DiscreteFiniteRandomExperiment::new(vec![0.9, 1.1], &[0.5, 0.5]);
+        let res = returns.simulate_portfolio(&mut rng, 100.0, 10, 20_000);
+
+        // E[V] = 100 * 1.0^10
+        assert!((res.mean_final_value - 100.0).abs() < 1.0);
+        let var95 = res.value_at_risk(0.05);
+        let below = res.final_values.iter().filter(|v| **v < var95).count() as f64 / 20_000.0;
+        assert!(below <= 0.05);
+        // at least 8 down moves out of 10 has probability 56/1024 ~ 0.055, at least 9 ~ 0.011
+        assert!((var95 - 100.0 * 0.9f64.powi(8) * 1.1f64.powi(2)).abs() < 1e-9);
+        // a loss needs at least 5 down moves: P = 638/1024
+        assert!((res.probability_of_loss - 638.0 / 1024.0).abs() < 0.02);
+    }
+
+    #[test]
+    fn voter_model_check() {
+        let mut rng = StdRng::seed_from_u64(114);
+        let opinions = DiscreteFiniteRandomExperiment::new(vec!['A', 'B'], &[0.3, 0.7]);
+
+        let mut a_wins = 0;
+        for _ in 0..2_000 {
+            let res = opinions.simulate_voter_model(&mut rng, 20, 1_000_000);
+            let t = res.steps_to_consensus.expect("consensus is reached");
+            assert_eq!(res.opinion_counts.len(), t + 1);
+            if res.consensus_opinion == Some(0) {
+                a_wins += 1;
+            }
+        }
+        assert!((a_wins as f64 / 2_000.0 - 0.3).abs() < 0.03);
+    }
+
+    #[test]
+    fn cumulative_sum_check() {
+        let mut rng = StdRng::seed_from_u64(116);
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[1.0; 6]);
+
+        let path = dice.cumulative_sum_process(&mut rng, 10);
+        assert_eq!(path.len(), 10);
+        assert!(path.windows(2).all(|w| w[1] - w[0] >= 1.0 && w[1] - w[0] <= 6.0));
+
+        let (means, variances) = dice.cumulative_sum_stats(&mut rng, 20, 10_000);
+        for t in [1, 10, 20] {
+            assert!((means[t - 1] - 3.5 * t as f64).abs() < 0.05 * t as f64);
+            assert!((variances[t - 1] / (35.0 / 12.0 * t as f64) - 1.0).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn branching_random_walk_check() {
+        let mut rng = StdRng::seed_from_u64(133);
+        let origin = DiscreteFiniteRandomExperiment::new(vec![0.0], &[1.0]);
+        let two_children = DiscreteFiniteRandomExperiment::new(vec![2], &[1.0]);
+        let steps = DiscreteFiniteRandomExperiment::new(vec![-1.0, 0.0, 1.0], &[1.0, 1.0, 1.0]);
+
+        let generations = origin.simulate_branching_random_walk(&two_children, &steps, &mut rng, 14);
+        assert_eq!(generations.len(), 15);
+        assert_eq!(generations[14].len(), 1 << 14);
+
+        let front = frontier(&generations);
+        assert_eq!(front[0], 0.0);
+        let (speed_7, speed_14) = (front[7] / 7.0, front[14] / 14.0);
+        assert!(front[14] > front[7]);
+        assert!((0.4..=1.0).contains(&speed_7) && (0.4..=1.0).contains(&speed_14));
+
+        let none = DiscreteFiniteRandomExperiment::new(vec![0], &[1.0]);
+        let generations = origin.simulate_branching_random_walk(&none, &steps, &mut rng, 3);
+        assert_eq!(frontier(&generations)[1], f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn renewal_reward_check() {
+        let mut rng = StdRng::seed_from_u64(137);
+        let omega: Vec<usize> = (1..=60).collect();
+        let law: Vec<f64> = omega.iter().map(|k| 0.3 * 0.7f64.powi(*k as i32 - 1)).collect();
+        let geometric = DiscreteFiniteRandomExperiment::new(omega, &law);
+
+        let res = geometric.simulate_renewal_reward(&mut rng, |k| *k as f64, |k| if *k > 3 { 5.0 } else { 1.0 }, 10_000.0, 20);
+        assert!((res.estimated_long_run_reward_rate / res.theoretical_rate - 1.0).abs() < 0.02);
+
+        let res = geometric.simulate_renewal_reward(&mut rng, |k| *k as f64, |_| 1.0, 10_000.0, 20);
+        assert!((res.theoretical_rate - 0.3).abs() < 1e-6);
+        assert!((res.estimated_long_run_reward_rate - 0.3).abs() < 0.005);
+    }
+
+    #[test]
+    #[should_panic(expected = "inter-renewal times must be positive")]
+    fn renewal_reward_zero_time() {
+        let exp = DiscreteFiniteRandomExperiment::new(vec![0.0, 1.0], &[1.0, 1.0]);
+        exp.simulate_renewal_reward(&mut StdRng::seed_from_u64(137), |x| *x, |_| 1.0, 10.0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_time must be positive and finite")]
+    fn renewal_reward_nan_time() {
+        let exp = DiscreteFiniteRandomExperiment::new(vec![1.0], &[1.0]);
+        exp.simulate_renewal_reward(&mut StdRng::seed_from_u64(137), |x| *x, |_| 1.0, f64::NAN, 1);
+    }
+
+    #[test]
+    fn compound_poisson_check() {
+        let mut rng = StdRng::seed_from_u64(138);
+        let tick = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.02, 0.98]);
+        let jumps = DiscreteFiniteRandomExperiment::new(vec![1.0, 2.0, 3.0], &[1.0, 1.0, 1.0]);
+
+        let path = tick.simulate_compound_poisson(&jumps, &mut rng, 2.0, 10.0);
+        assert!(path.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1));
+        assert!(path.last().unwrap().0 <= 10.0);
+
+        let totals: Vec<f64> = (0..1_000)
+            .map(|_| tick.simulate_compound_poisson(&jumps, &mut rng, 2.0, 10.0).last().map_or(0.0, |j| j.1))
+            .collect();
+        let (mean, variance) = mean_and_variance(&totals);
+
+        let (m, v) = DiscreteFiniteRandomExperiment::compound_poisson_moments(&jumps, 2.0, 10.0);
+        assert!((m - 40.0).abs() < 1e-9);
+        assert!((v - 280.0 / 3.0).abs() < 1e-9);
+        assert!((mean / m - 1.0).abs() < 0.03);
+        assert!((variance / v - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    #[should_panic(expected = "lambda must be positive")]
+    fn compound_poisson_zero_rate() {
+        let tick = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.1, 0.9]);
+        let jumps = DiscreteFiniteRandomExperiment::new(vec![1.0], &[1.0]);
+        tick.simulate_compound_poisson(&jumps, &mut StdRng::seed_from_u64(138), 0.0, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "positive probability to true")]
+    fn compound_poisson_never_true() {
+        let tick = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.0, 1.0]);
+        let jumps = DiscreteFiniteRandomExperiment::new(vec![1.0], &[1.0]);
+        tick.simulate_compound_poisson(&jumps, &mut StdRng::seed_from_u64(138), 2.0, 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "time_horizon must be finite and non negative")]
+    fn compound_poisson_infinite_horizon() {
+        let tick = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.1, 0.9]);
+        let jumps = DiscreteFiniteRandomExperiment::new(vec![1.0], &[1.0]);
+        tick.simulate_compound_poisson(&jumps, &mut StdRng::seed_from_u64(138), 2.0, f64::INFINITY);
+    }
+
+    #[test]
+    fn ruin_check() {
+        let mut rng = StdRng::seed_from_u64(156);
+        let claims = DiscreteFiniteRandomExperiment::new(vec![1.0, 2.0], &[0.5, 0.5]);
+        assert!(claims.cramer_lundberg_bound(5.0, 1.5).is_none());
+        let zero = DiscreteFiniteRandomExperiment::new(vec![0.0], &[1.0]);
+        assert!(zero.cramer_lundberg_bound(5.0, 1.0).is_none());
+
+        let bound = claims.cramer_lundberg_bound(0.0, 2.0).unwrap();
+        assert!((bound - 1.0).abs() < 1e-12);
+        for u in [0.0, 2.0, 5.0] {
+            let bound = claims.cramer_lundberg_bound(u, 2.0).unwrap();
+            let ruin = claims.simulate_ruin(&mut rng, u, 2.0, 200, 5_000);
+            assert!(ruin <= bound + 0.01, "{ruin} {bound}");
+        }
+        // the adjustment coefficient r solves 0.5 e^r + 0.5 e^2r = 1 + 2 r
+        let r = -claims.cramer_lundberg_bound(1.0, 2.0).unwrap().ln();
+        assert!((0.5 * r.exp() + 0.5 * (2.0 * r).exp() - 1.0 - 2.0 * r).abs() < 1e-9);
+    }
+
+    #[test]
+    fn multi_server_queue_check() {
+        let mut rng = StdRng::seed_from_u64(163);
+        // M/D/1 near saturation: mean wait rho / (2 (1 - rho)) = 4.5
+        let deterministic = DiscreteFiniteRandomExperiment::new(vec![1.0], &[1.0]);
+        let one = deterministic.simulate_multi_server_queue(&mut rng, 1, 0.9, 200_000);
+        assert!((one.mean_waiting_time / 4.5 - 1.0).abs() < 0.1);
+        assert!((one.server_utilization[0] - 0.9).abs() < 0.01);
+        let two = deterministic.simulate_multi_server_queue(&mut rng, 2, 0.9, 200_000);
+        assert!(two.mean_waiting_time < 0.5 * one.mean_waiting_time);
+        assert_eq!(two.server_utilization.len(), 2);
+
+        // discretized exponential service against Erlang C
+        let h = 0.02;
+        let omega: Vec<f64> = (0..1_000).map(|k| (k as f64 + 0.5) * h).collect();
+        let weights: Vec<f64> = omega.iter().map(|x| (-x).exp()).collect();
+        let exponential = DiscreteFiniteRandomExperiment::new(omega, &weights);
+        let service_rate = 1.0 / exponential.expected_value();
+        let res = exponential.simulate_multi_server_queue(&mut rng, 2, 1.5, 200_000);
+        let erlang = erlang_c_waiting_time(2, 1.5, service_rate);
+        assert!((erlang_c_waiting_time(2, 1.5, 1.0) - 9.0 / 7.0).abs() < 1e-12);
+        assert!((res.mean_waiting_time / erlang - 1.0).abs() < 0.1);
+        assert!((res.mean_queue_length - 1.5 * res.mean_waiting_time).abs() < 1e-12);
+        assert!(erlang_c_waiting_time(1, 2.0, 1.0).is_infinite());
+    }
+
+    #[test]
+    fn spatial_birth_death_check() {
+        let mut rng = StdRng::seed_from_u64(166);
+        type Exp = DiscreteFiniteRandomExperiment<usize>;
+        let (birth, death) = (0.1, 0.02);
+        let population = Exp::simulate_spatial_birth_death(&mut rng, 10, birth, death, 300_000);
+        assert_eq!(population.len(), 300_000);
+
+        let settled = &population[1_000..];
+        let mean = settled.iter().sum::<usize>() as f64 / settled.len() as f64;
+        assert!((mean - 5.0).abs() < 0.15);
+
+        let n_max = 30;
+        let equilibrium = Exp::birth_death_equilibrium(birth, death, n_max);
+        assert!((equilibrium.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        let mut empirical = vec![0.0; n_max];
+        for n in settled {
+            empirical[(*n).min(n_max - 1)] += 1.0 / settled.len() as f64;
+        }
+        assert!(total_variation_distance(&empirical, &equilibrium) < 0.04);
+    }
+}
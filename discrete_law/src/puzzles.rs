@@ -0,0 +1,522 @@
+//! Classic probability puzzles simulated with an experiment.
+
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::seq::SliceRandom;
+
+use crate::statistics::mean_and_variance;
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Strategy for the secretary problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretaryStrategy {
+    /// Skip the first `n/e` candidates then take the first one better than all seen.
+    Optimal,
+    /// Skip the first `k` candidates then take the first one better than all seen.
+    SkipK(usize),
+    /// Take a candidate uniformly at random.
+    Random,
+}
+
+/// Secretary problem, candidate values are i.i.d. draws from `self`.
+impl<T: PartialOrd + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Probability of selecting the best candidate among `n_candidates` with `strategy`.
+    ///
+    /// When no candidate beats the skipped ones, the last candidate is taken.
+    /// With ties, selecting any candidate with the best value counts as a success.
+    pub fn secretary_problem_simulation<R: Rng>(&self, rng: &mut R, n_candidates: usize, strategy: SecretaryStrategy, repetitions: usize) -> f64 {
+        assert!(n_candidates > 0, "at least one candidate is required");
+        let skip = match strategy {
+            SecretaryStrategy::Optimal => (n_candidates as f64 / std::f64::consts::E).round() as usize,
+            SecretaryStrategy::SkipK(k) => k.min(n_candidates - 1),
+            SecretaryStrategy::Random => 0,
+        };
+
+        let mut success = 0;
+        for _ in 0..repetitions {
+            let candidates: Vec<T> = (0..n_candidates).map(|_| self.sample(rng)).collect();
+
+            let chosen = match strategy {
+                SecretaryStrategy::Random => rng.random_range(0..n_candidates),
+                _ => {
+                    let mut best_seen: Option<&T> = None;
+                    for c in &candidates[..skip] {
+                        if best_seen.is_none_or(|b| c > b) {
+                            best_seen = Some(c);
+                        }
+                    }
+                    (skip..n_candidates)
+                        .find(|i| best_seen.is_none_or(|b| candidates[*i] > *b))
+                        .unwrap_or(n_candidates - 1)
+                }
+            };
+
+            if !candidates.iter().any(|c| c > &candidates[chosen]) {
+                success += 1;
+            }
+        }
+        success as f64 / repetitions as f64
+    }
+}
+
+
+/// Result of `ballot_problem_simulation`.
+#[derive(Debug, Clone)]
+pub struct BallotResult {
+    /// Estimated probability that A is strictly ahead during the whole count.
+    pub probability_a_leads: f64,
+    /// Ballot theorem value `(a-b)/(a+b)` for the expected tallies `a = p*n`, `b = (1-p)*n`
+    /// (0 when A is not expected to win).
+    pub theoretical: f64,
+}
+
+/// Ballot problem, `self` is a single vote (`true` for A, `false` for B).
+impl DiscreteFiniteRandomExperiment<bool> {
+    /// Probability of a `true` outcome.
+    pub fn probability_of_true(&self) -> f64 {
+        self.omega.iter()
+            .zip(self.distribution.probabilities())
+            .filter(|(o, _)| **o)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    /// Count `n_votes` votes `repetitions` times and estimate the probability that
+    /// A is strictly ahead of B after each vote.
+    pub fn ballot_problem_simulation<R: Rng>(&self, rng: &mut R, n_votes: usize, repetitions: usize) -> BallotResult {
+        let mut leads = 0;
+        for _ in 0..repetitions {
+            let mut margin: i64 = 0;
+            let always_ahead = (0..n_votes).all(|_| {
+                margin += if self.sample(rng) { 1 } else { -1 };
+                margin > 0
+            });
+            if always_ahead {
+                leads += 1;
+            }
+        }
+
+        let p = self.probability_of_true();
+        let (a, b) = (p * n_votes as f64, (1.0 - p) * n_votes as f64);
+        BallotResult {
+            probability_a_leads: leads as f64 / repetitions as f64,
+            theoretical: ((a - b) / (a + b)).max(0.0),
+        }
+    }
+}
+
+/// Result of `simulate_threshold_stopping`.
+#[derive(Debug, Clone)]
+pub struct ThresholdResult {
+    pub mean_payoff: f64,
+    /// Mean number of observations before stopping.
+    pub mean_steps: f64,
+    /// Fraction of runs stopped at a value reaching the threshold, the last observation included
+    /// (the other runs take a last value below the threshold).
+    pub stopping_probability: f64,
+}
+
+/// Optimal stopping of i.i.d. draws of `self` with payoff `value_fn`.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Stop at the first value at least `threshold`, or take the value at step `max_steps`.
+    pub fn simulate_threshold_stopping<R: Rng, V: Fn(&T) -> f64>(&self, rng: &mut R, value_fn: V, threshold: f64, max_steps: usize, repetitions: usize) -> ThresholdResult {
+        assert!(max_steps > 0, "at least one observation is required");
+        let (mut payoff, mut steps, mut stopped) = (0.0, 0, 0);
+        for _ in 0..repetitions {
+            for t in 1..=max_steps {
+                let v = value_fn(&self.omega[self.distribution.sample(rng)]);
+                if v >= threshold || t == max_steps {
+                    payoff += v;
+                    steps += t;
+                    if v >= threshold {
+                        stopped += 1;
+                    }
+                    break;
+                }
+            }
+        }
+        let r = repetitions as f64;
+        ThresholdResult {
+            mean_payoff: payoff / r,
+            mean_steps: steps as f64 / r,
+            stopping_probability: stopped as f64 / r,
+        }
+    }
+
+    /// Optimal threshold for the first of `max_steps` observations, by backward induction:
+    /// with `k` observations left the optimal value is `V_k = E[max(v(X), V_(k-1))]`, `V_1 = E[v(X)]`,
+    /// and a value is accepted when it is at least the value `V_(k-1)` of continuing.
+    pub fn find_optimal_threshold(&self, value_fn: &dyn Fn(&T) -> f64, max_steps: usize) -> f64 {
+        assert!(max_steps > 0, "at least one observation is required");
+        let values: Vec<(f64, f64)> = self.omega.iter()
+            .map(value_fn)
+            .zip(self.distribution.probabilities())
+            .collect();
+        if max_steps == 1 {
+            return f64::NEG_INFINITY;
+        }
+        let mut continuation: f64 = values.iter().map(|(v, p)| v * p).sum();
+        for _ in 2..max_steps {
+            continuation = values.iter().map(|(v, p)| v.max(continuation) * p).sum();
+        }
+        continuation
+    }
+}
+
+/// Result of `simulate_two_envelope`.
+#[derive(Debug, Clone)]
+pub struct TwoEnvelopeResult {
+    /// Mean amount obtained when always switching.
+    pub mean_gain_switch: f64,
+    /// Mean amount obtained when never switching.
+    pub mean_gain_stay: f64,
+    /// Fraction of trials where switching gives the larger amount.
+    pub beneficial_switch_fraction: f64,
+    /// Expected amount of both strategies, `1.5 * E[X]`.
+    pub theoretical_mean_gain: f64,
+    /// Whether the mean difference between switching and staying is within 3 standard errors of 0.
+    pub strategies_agree: bool,
+}
+
+/// Two envelope problem, `self` is the law of the smaller amount `X` (the other being `2X`).
+impl<T: Into<f64> + Clone> DiscreteFiniteRandomExperiment<T> {
+    pub fn simulate_two_envelope<R: Rng>(&self, rng: &mut R, n_trials: usize) -> TwoEnvelopeResult {
+        assert!(n_trials >= 2, "at least two trials are required to estimate the variance");
+        let (mut switch, mut stay, mut beneficial) = (0.0, 0.0, 0);
+        let mut differences = Vec::with_capacity(n_trials);
+        for _ in 0..n_trials {
+            let x: f64 = self.sample(rng).into();
+            let (picked, other) = if rng.random::<bool>() { (x, 2.0 * x) } else { (2.0 * x, x) };
+            stay += picked;
+            switch += other;
+            if other > picked {
+                beneficial += 1;
+            }
+            differences.push(other - picked);
+        }
+
+        let n = n_trials as f64;
+        let (diff_mean, diff_variance) = mean_and_variance(&differences);
+        TwoEnvelopeResult {
+            mean_gain_switch: switch / n,
+            mean_gain_stay: stay / n,
+            beneficial_switch_fraction: beneficial as f64 / n,
+            theoretical_mean_gain: 1.5 * self.expected_value(),
+            strategies_agree: diff_mean.abs() <= 3.0 * (diff_variance / n).sqrt(),
+        }
+    }
+}
+
+/// Result of the Monty Hall simulations.
+#[derive(Debug, Clone)]
+pub struct MontyHallResult {
+    pub switch_win_rate: f64,
+    pub stay_win_rate: f64,
+}
+
+/// Monty Hall game, `self` is the law of the door hiding the prize.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Standard game: the host opens one losing door.
+    pub fn simulate_monty_hall<R: Rng>(&self, rng: &mut R, n_trials: usize) -> MontyHallResult {
+        self.simulate_monty_hall_revealing(rng, n_trials, 1)
+    }
+
+    /// Game with `omega.len()` doors where the host opens `revealed` losing doors among
+    /// the doors not picked, the switching player then picks uniformly among the remaining closed doors.
+    pub fn simulate_monty_hall_revealing<R: Rng>(&self, rng: &mut R, n_trials: usize, revealed: usize) -> MontyHallResult {
+        let n_doors = self.omega.len();
+        assert!(revealed + 2 <= n_doors, "the host must leave a door to switch to");
+        let (mut switch_wins, mut stay_wins) = (0, 0);
+        for _ in 0..n_trials {
+            let prize = self.distribution.sample(rng);
+            let pick = rng.random_range(0..n_doors);
+
+            let mut openable: Vec<usize> = (0..n_doors).filter(|d| *d != pick && *d != prize).collect();
+            openable.shuffle(rng);
+            let opened = &openable[..revealed];
+
+            let closed: Vec<usize> = (0..n_doors).filter(|d| *d != pick && !opened.contains(d)).collect();
+            let switch = closed[rng.random_range(0..closed.len())];
+
+            if pick == prize {
+                stay_wins += 1;
+            }
+            if switch == prize {
+                switch_wins += 1;
+            }
+        }
+        MontyHallResult {
+            switch_win_rate: switch_wins as f64 / n_trials as f64,
+            stay_win_rate: stay_wins as f64 / n_trials as f64,
+        }
+    }
+}
+
+/// Strategy of the prisoners in the 100 prisoners problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrisonerStrategy {
+    /// Open the box of one's own number, then the box of the number found, and so on.
+    Cycle,
+    /// Open boxes uniformly at random.
+    Random,
+}
+
+/// Permutation puzzles, permutations are draws without replacement from a uniform experiment.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Uniform random permutation of `0..n`.
+    pub fn uniform_permutation<R: Rng>(rng: &mut R, n: usize) -> Vec<usize> {
+        if n == 0 {
+            return Vec::new();
+        }
+        let uniform = DiscreteFiniteRandomExperiment::from_fn(n, |_| 1.0).unwrap();
+        uniform.sample_without_replacement(rng, n)
+    }
+
+    /// Probability that all `n_prisoners` prisoners find their number in the shuffled boxes
+    /// opening at most `max_opens` boxes each.
+    pub fn simulate_prisoner_problem<R: Rng>(rng: &mut R, n_prisoners: usize, max_opens: usize, strategy: PrisonerStrategy, repetitions: usize) -> f64 {
+        let max_opens = max_opens.min(n_prisoners);
+        let mut success = 0;
+        for _ in 0..repetitions {
+            let boxes = Self::uniform_permutation(rng, n_prisoners);
+            let all_found = (0..n_prisoners).all(|prisoner| match strategy {
+                PrisonerStrategy::Cycle => {
+                    let mut b = prisoner;
+                    (0..max_opens).any(|_| {
+                        b = boxes[b];
+                        b == prisoner
+                    })
+                }
+                PrisonerStrategy::Random => Self::uniform_permutation(rng, n_prisoners)[..max_opens]
+                    .iter()
+                    .any(|b| boxes[*b] == prisoner),
+            });
+            if all_found {
+                success += 1;
+            }
+        }
+        success as f64 / repetitions as f64
+    }
+
+    /// Probability that no guest gets their own hat back when `n` hats are returned
+    /// in uniform random order.
+    pub fn simulate_hat_check<R: Rng>(rng: &mut R, n: usize, repetitions: usize) -> f64 {
+        let derangements = (0..repetitions)
+            .filter(|_| Self::uniform_permutation(rng, n).iter().enumerate().all(|(guest, hat)| guest != *hat))
+            .count();
+        derangements as f64 / repetitions as f64
+    }
+
+    /// Probability of a derangement of `n` items, `sum_(k=0..n) (-1)^k / k!`.
+    pub fn theoretical_derangement_probability(n: usize) -> f64 {
+        let mut term = 1.0;
+        let mut total = 1.0;
+        for k in 1..=n {
+            term *= -1.0 / k as f64;
+            total += term;
+        }
+        total
+    }
+}
+
+/// Result of `simulate_occupancy`.
+#[derive(Debug, Clone)]
+pub struct OccupancyResult {
+    pub mean_empty_bins: f64,
+    /// Mean number of balls in the fullest bin.
+    pub mean_max_load: f64,
+    pub probability_all_occupied: f64,
+}
+
+/// Balls thrown into bins uniformly at random.
+impl DiscreteFiniteRandomExperiment<usize> {
+    /// Throw `n_balls` balls in `n_bins` bins, `repetitions` times.
+    pub fn simulate_occupancy<R: Rng>(rng: &mut R, n_bins: usize, n_balls: usize, repetitions: usize) -> OccupancyResult {
+        let bins = DiscreteFiniteRandomExperiment::from_fn(n_bins, |_| 1.0).unwrap();
+        let (mut empty, mut max_load, mut all_occupied) = (0, 0, 0);
+        for _ in 0..repetitions {
+            let mut loads = vec![0usize; n_bins];
+            (0..n_balls).for_each(|_| loads[bins.sample(rng)] += 1);
+            let e = loads.iter().filter(|l| **l == 0).count();
+            empty += e;
+            max_load += loads.iter().max().unwrap();
+            if e == 0 {
+                all_occupied += 1;
+            }
+        }
+        let r = repetitions as f64;
+        OccupancyResult {
+            mean_empty_bins: empty as f64 / r,
+            mean_max_load: max_load as f64 / r,
+            probability_all_occupied: all_occupied as f64 / r,
+        }
+    }
+
+    /// Expected number of empty bins `n * (1 - 1/n)^m`.
+    pub fn theoretical_empty_bins(n_bins: usize, n_balls: usize) -> f64 {
+        let n = n_bins as f64;
+        n * (1.0 - 1.0 / n).powi(n_balls as i32)
+    }
+
+    /// Probability that no bin is empty, by inclusion-exclusion:
+    /// `sum_k (-1)^k C(n, k) (1 - k/n)^m`.
+    pub fn theoretical_probability_all_occupied(n_bins: usize, n_balls: usize) -> f64 {
+        let n = n_bins as f64;
+        let mut binomial = 1.0;
+        let mut total = 0.0;
+        for k in 0..=n_bins {
+            if k > 0 {
+                binomial *= (n_bins - k + 1) as f64 / k as f64;
+            }
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            total += sign * binomial * (1.0 - k as f64 / n).powi(n_balls as i32);
+        }
+        total
+    }
+
+    /// Asymptotic maximum load `ln n / ln ln n` when as many balls as bins are thrown.
+    pub fn approximate_max_load(n_bins: usize) -> f64 {
+        let n = n_bins as f64;
+        n.ln() / n.ln().ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn secretary_check() {
+        let mut rng = StdRng::seed_from_u64(106);
+        let values = DiscreteFiniteRandomExperiment::from_fn(10_000, |_| 1.0).unwrap();
+
+        let p = values.secretary_problem_simulation(&mut rng, 100, SecretaryStrategy::Optimal, 5_000);
+        assert!((p - 1.0 / std::f64::consts::E).abs() < 0.025);
+
+        let p = values.secretary_problem_simulation(&mut rng, 100, SecretaryStrategy::Random, 5_000);
+        assert!((p - 0.01).abs() < 0.005);
+
+        let p = values.secretary_problem_simulation(&mut rng, 100, SecretaryStrategy::SkipK(0), 1_000);
+        assert!((p - 0.01).abs() < 0.01);
+    }
+
+    #[test]
+    fn ballot_check() {
+        let mut rng = StdRng::seed_from_u64(108);
+        let fair = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.5, 0.5]);
+        let res = fair.ballot_problem_simulation(&mut rng, 100, 10_000);
+        assert_eq!(res.theoretical, 0.0);
+        assert!(res.probability_a_leads < 0.06);
+
+        let biased = DiscreteFiniteRandomExperiment::new(vec![true, false], &[0.7, 0.3]);
+        let res = biased.ballot_problem_simulation(&mut rng, 100, 10_000);
+        assert!((res.theoretical - 0.4).abs() < 1e-12);
+        assert!((res.probability_a_leads - 0.4).abs() < 0.03);
+    }
+
+    #[test]
+    fn threshold_stopping_check() {
+        let mut rng = StdRng::seed_from_u64(122);
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let value = |x: &i32| *x as f64;
+
+        // with two draws, keep the first one when it beats the mean 3.5, which is the median
+        let threshold = dice.find_optimal_threshold(&value, 2);
+        assert!((threshold - 3.5).abs() < 1e-12);
+        let res = dice.simulate_threshold_stopping(&mut rng, value, threshold, 2, 50_000);
+        assert!((res.mean_payoff - 4.25).abs() < 0.03);
+        // 1/2 stop at the first draw, 1/4 at a second draw of at least 4
+        assert!((res.stopping_probability - 0.75).abs() < 0.01);
+        assert!((res.mean_steps - 1.5).abs() < 0.01);
+
+        // more observations raise the threshold
+        let three = dice.find_optimal_threshold(&value, 3);
+        assert!((three - 4.25).abs() < 1e-12);
+        let bad = dice.simulate_threshold_stopping(&mut rng, value, 1.0, 3, 50_000);
+        let good = dice.simulate_threshold_stopping(&mut rng, value, three, 3, 50_000);
+        assert!(good.mean_payoff > bad.mean_payoff);
+    }
+
+    #[test]
+    fn two_envelope_check() {
+        let mut rng = StdRng::seed_from_u64(123);
+        let omega: Vec<f64> = (1..=20).map(|k| k as f64).collect();
+        let law: Vec<f64> = (1..=20).map(|k| 0.5f64.powi(k)).collect();
+        let geometric = DiscreteFiniteRandomExperiment::new(omega, &law);
+
+        let res = geometric.simulate_two_envelope(&mut rng, 100_000);
+        assert!(res.strategies_agree);
+        assert!((res.mean_gain_switch / res.mean_gain_stay - 1.0).abs() < 0.02);
+        assert!((res.mean_gain_stay / res.theoretical_mean_gain - 1.0).abs() < 0.02);
+        assert!((res.beneficial_switch_fraction - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least two trials")]
+    fn two_envelope_single_trial() {
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1.0, 2.0], &[1.0, 1.0]);
+        dice.simulate_two_envelope(&mut StdRng::seed_from_u64(123), 1);
+    }
+
+    #[test]
+    fn monty_hall_check() {
+        let mut rng = StdRng::seed_from_u64(124);
+        let doors = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3], &[1.0, 1.0, 1.0]);
+        let res = doors.simulate_monty_hall(&mut rng, 100_000);
+        assert!((res.switch_win_rate - 2.0 / 3.0).abs() < 0.01);
+        assert!((res.stay_win_rate - 1.0 / 3.0).abs() < 0.01);
+
+        // 5 doors, 2 opened: switching wins with probability 4/5 * 1/2
+        let doors = DiscreteFiniteRandomExperiment::from_fn(5, |_| 1.0).unwrap();
+        let res = doors.simulate_monty_hall_revealing(&mut rng, 100_000, 2);
+        assert!((res.switch_win_rate - 0.4).abs() < 0.01);
+        assert!((res.stay_win_rate - 0.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn prisoner_check() {
+        let mut rng = StdRng::seed_from_u64(125);
+        // exact value 1 - (1/51 + ... + 1/100) ~ 0.3118
+        let exact = 1.0 - (51..=100).map(|k| 1.0 / k as f64).sum::<f64>();
+        let p = DiscreteFiniteRandomExperiment::simulate_prisoner_problem(&mut rng, 100, 50, PrisonerStrategy::Cycle, 5_000);
+        assert!((p - exact).abs() < 0.02);
+
+        let p = DiscreteFiniteRandomExperiment::simulate_prisoner_problem(&mut rng, 100, 50, PrisonerStrategy::Random, 1_000);
+        assert_eq!(p, 0.0);
+        let p = DiscreteFiniteRandomExperiment::simulate_prisoner_problem(&mut rng, 2, 1, PrisonerStrategy::Cycle, 10_000);
+        assert!((p - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn hat_check_check() {
+        let mut rng = StdRng::seed_from_u64(126);
+        type Exp = DiscreteFiniteRandomExperiment<usize>;
+        assert_eq!(Exp::theoretical_derangement_probability(1), 0.0);
+        assert!((Exp::theoretical_derangement_probability(3) - 1.0 / 3.0).abs() < 1e-12);
+        assert!((Exp::theoretical_derangement_probability(20) - (-1.0f64).exp()).abs() < 1e-12);
+
+        let theory = Exp::theoretical_derangement_probability(10);
+        let p = Exp::simulate_hat_check(&mut rng, 10, 50_000);
+        assert!((p - theory).abs() < 0.01);
+    }
+
+    #[test]
+    fn occupancy_check() {
+        let mut rng = StdRng::seed_from_u64(127);
+        type Exp = DiscreteFiniteRandomExperiment<usize>;
+
+        let res = Exp::simulate_occupancy(&mut rng, 100, 100, 5_000);
+        let theory = Exp::theoretical_empty_bins(100, 100);
+        assert!((theory - 100.0 / std::f64::consts::E).abs() < 0.2);
+        assert!((res.mean_empty_bins - theory).abs() < 0.2);
+        assert!(res.mean_max_load >= 3.0 && res.mean_max_load < 6.0);
+        assert_eq!(res.probability_all_occupied, 0.0);
+
+        let res = Exp::simulate_occupancy(&mut rng, 5, 20, 20_000);
+        let theory = Exp::theoretical_probability_all_occupied(5, 20);
+        assert!((res.probability_all_occupied - theory).abs() < 0.01);
+        assert!((Exp::theoretical_probability_all_occupied(3, 2)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,122 @@
+//! Records in sequences of i.i.d. draws.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::DiscreteFiniteRandomExperiment;
+
+/// Records (strict new maxima, the first draw being one) of i.i.d. draws of `self`.
+impl<T: PartialOrd + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Exact expected number of records in `n` draws:
+    /// `sum_t sum_x p(x) * P(X < x)^(t-1)`.
+    pub fn expected_number_of_records(&self, n: usize) -> f64 {
+        let p = self.distribution.probabilities();
+        let below: Vec<f64> = self.omega.iter()
+            .map(|x| self.omega.iter().zip(&p).filter(|(y, _)| *y < x).map(|(_, q)| q).sum())
+            .collect();
+        p.iter().zip(&below)
+            .map(|(px, f)| px * (0..n as i32).map(|t| f.powi(t)).sum::<f64>())
+            .sum()
+    }
+
+    /// Mean number of records in `n` draws over `repetitions` sequences.
+    pub fn simulate_expected_records<R: Rng>(&self, rng: &mut R, n: usize, repetitions: usize) -> f64 {
+        let mut total = 0;
+        for _ in 0..repetitions {
+            let mut best: Option<T> = None;
+            for _ in 0..n {
+                let x = self.sample(rng);
+                if best.as_ref().is_none_or(|b| x > *b) {
+                    total += 1;
+                    best = Some(x);
+                }
+            }
+        }
+        total as f64 / repetitions as f64
+    }
+}
+
+
+/// Successive minima (strict new minima, the first draw being one) of i.i.d. draws of `self`.
+impl<T: Ord + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Indices and values of the successive minima among `n` draws.
+    pub fn simulate_successive_minima<R: Rng>(&self, rng: &mut R, n: usize) -> Vec<(usize, T)> {
+        let mut minima: Vec<(usize, T)> = Vec::new();
+        for i in 0..n {
+            let x = self.sample(rng);
+            if minima.last().is_none_or(|(_, m)| x < *m) {
+                minima.push((i, x));
+            }
+        }
+        minima
+    }
+
+    /// Exact expected number of successive minima in `n` draws:
+    /// `sum_t sum_x p(x) * P(X > x)^(t-1)`.
+    pub fn expected_number_of_minima(&self, n: usize) -> f64 {
+        let mut law: Vec<(&T, f64)> = self.omega.iter().zip(self.distribution.probabilities()).collect();
+        law.sort_by(|a, b| b.0.cmp(a.0));
+
+        // walk from the largest outcome down, `above` is P(X > x) for the current value
+        let (mut expected, mut above, mut i) = (0.0, 0.0, 0);
+        while i < law.len() {
+            let j = i + law[i..].iter().take_while(|(y, _)| *y == law[i].0).count();
+            let px: f64 = law[i..j].iter().map(|(_, p)| p).sum();
+            expected += px * (0..n as i32).map(|t| f64::powi(above, t)).sum::<f64>();
+            above += px;
+            i = j;
+        }
+        expected
+    }
+
+    /// Mean number of successive minima in `n` draws over `repetitions` sequences.
+    pub fn simulate_expected_minima<R: Rng>(&self, rng: &mut R, n: usize, repetitions: usize) -> f64 {
+        let total: usize = (0..repetitions).map(|_| self.simulate_successive_minima(rng, n).len()).sum();
+        total as f64 / repetitions as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn records_check() {
+        let mut rng = StdRng::seed_from_u64(121);
+        let harmonic: f64 = (1..=10).map(|k| 1.0 / k as f64).sum();
+
+        let mut previous = 0.0;
+        for k in [2, 10, 100, 1000] {
+            let uniform = DiscreteFiniteRandomExperiment::from_fn(k, |_| 1.0).unwrap();
+            let e = uniform.expected_number_of_records(10);
+            assert!(e > previous && e < harmonic);
+            previous = e;
+        }
+        assert!((harmonic - previous).abs() < 0.01);
+
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let exact = dice.expected_number_of_records(10);
+        assert!((dice.expected_number_of_records(1) - 1.0).abs() < 1e-12);
+        assert!((dice.simulate_expected_records(&mut rng, 10, 20_000) - exact).abs() < 0.03);
+    }
+
+    #[test]
+    fn successive_minima_check() {
+        let mut rng = StdRng::seed_from_u64(141);
+        let harmonic: f64 = (1..=10).map(|k| 1.0 / k as f64).sum();
+        let uniform = DiscreteFiniteRandomExperiment::from_fn(10_000, |_| 1.0).unwrap();
+
+        assert!((uniform.expected_number_of_minima(10) - harmonic).abs() < 0.01);
+        assert!((uniform.simulate_expected_minima(&mut rng, 10, 20_000) - harmonic).abs() < 0.03);
+
+        // a fair die is symmetric: as many expected minima as records
+        let dice = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        assert!((dice.expected_number_of_minima(10) - dice.expected_number_of_records(10)).abs() < 1e-12);
+
+        let minima = uniform.simulate_successive_minima(&mut rng, 100);
+        assert_eq!(minima[0].0, 0);
+        assert!(minima.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 > w[1].1));
+    }
+}
@@ -0,0 +1,309 @@
+//! Resampling methods driven by uniform experiments over data indices.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::statistics::mean_and_variance;
+use crate::{DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment};
+
+/// Result of `permutation_test`.
+#[derive(Debug, Clone)]
+pub struct PermutationTestResult {
+    pub observed_statistic: f64,
+    pub null_mean: f64,
+    pub null_variance: f64,
+    /// Two-sided p-value `(1 + #{|s| >= |observed|}) / (1 + n_permutations)`.
+    pub p_value: f64,
+}
+
+/// Permutation test, `self` is not used.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Pool both groups, split random permutations of the pool (uniform draws without replacement
+    /// over the indices) in groups of the original sizes and compute the null distribution of `statistic`.
+    pub fn permutation_test<R: Rng, S: Fn(&[T], &[T]) -> f64>(&self, rng: &mut R, group_a: Vec<T>, group_b: Vec<T>, statistic: S, n_permutations: usize) -> PermutationTestResult {
+        let observed = statistic(&group_a, &group_b);
+        let n_a = group_a.len();
+        let pool: Vec<T> = group_a.into_iter().chain(group_b).collect();
+
+        let null: Vec<f64> = (0..n_permutations)
+            .map(|_| {
+                let permuted: Vec<T> = DiscreteFiniteRandomExperiment::uniform_permutation(rng, pool.len())
+                    .into_iter()
+                    .map(|i| pool[i].clone())
+                    .collect();
+                statistic(&permuted[..n_a], &permuted[n_a..])
+            })
+            .collect();
+
+        let (mean, variance) = mean_and_variance(&null);
+        let extreme = null.iter().filter(|s| s.abs() >= observed.abs()).count();
+        PermutationTestResult {
+            observed_statistic: observed,
+            null_mean: mean,
+            null_variance: variance,
+            p_value: (1 + extreme) as f64 / (1.0 + n_permutations as f64),
+        }
+    }
+}
+
+
+/// Experiment of the distinct values of `data` in order of first appearance, with weights their counts.
+fn empirical_experiment<T: Eq + Hash + Clone>(data: &[T]) -> DiscreteFiniteRandomExperiment<T> {
+    let mut index: HashMap<&T, usize> = HashMap::new();
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    for x in data {
+        let i = *index.entry(x).or_insert_with(|| {
+            counts.push((x.clone(), 0));
+            counts.len() - 1
+        });
+        counts[i].1 += 1;
+    }
+    DiscreteFiniteRandomExperiment::from_counts(counts)
+}
+
+/// Bootstrap, `self` is not used.
+impl<T: Eq + Hash + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Law of `statistic` over `n_bootstrap` resamples with replacement of `original_data`,
+    /// drawn from the empirical experiment of the data. The outcomes are the sorted distinct values of the statistic.
+    pub fn bootstrap_distribution<R: Rng, S: Fn(&[T]) -> f64>(&self, rng: &mut R, original_data: Vec<T>, statistic: S, n_bootstrap: usize) -> DiscreteFiniteRandomExperiment<f64> {
+        let empirical = empirical_experiment(&original_data);
+        let mut values: Vec<f64> = (0..n_bootstrap)
+            .map(|_| {
+                let resample: Vec<T> = (0..original_data.len()).map(|_| empirical.sample(rng)).collect();
+                statistic(&resample)
+            })
+            .collect();
+        values.sort_by(f64::total_cmp);
+        let mut counts: Vec<(f64, usize)> = Vec::new();
+        for v in values {
+            match counts.last_mut() {
+                Some((last, c)) if *last == v => *c += 1,
+                _ => counts.push((v, 1)),
+            }
+        }
+        DiscreteFiniteRandomExperiment::from_counts(counts)
+    }
+}
+
+/// Result of `jackknife_estimate`.
+#[derive(Debug, Clone)]
+pub struct JackknifeResult {
+    /// `(n - 1) * (mean of the leave-one-out statistics - statistic of the data)`.
+    pub bias_estimate: f64,
+    /// `sqrt((n - 1) / n * sum (s_i - mean)^2)` over the leave-one-out statistics `s_i`.
+    pub standard_error_estimate: f64,
+    /// Statistic of the data without the observation `i`.
+    pub leave_one_out: Vec<f64>,
+}
+
+/// Jackknife, `self` is not used.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Leave-one-out bias and standard error of `statistic` on `data` (at least two observations).
+    pub fn jackknife_estimate<S: Fn(&[T]) -> f64>(&self, data: &[T], statistic: S) -> JackknifeResult {
+        let n = data.len();
+        assert!(n >= 2, "the jackknife needs at least two observations");
+        let full = statistic(data);
+        let leave_one_out: Vec<f64> = (0..n)
+            .map(|i| {
+                let rest: Vec<T> = data[..i].iter().chain(&data[i + 1..]).cloned().collect();
+                statistic(&rest)
+            })
+            .collect();
+        let nf = n as f64;
+        let mean = leave_one_out.iter().sum::<f64>() / nf;
+        JackknifeResult {
+            bias_estimate: (nf - 1.0) * (mean - full),
+            standard_error_estimate: ((nf - 1.0) / nf * leave_one_out.iter().map(|s| (s - mean).powi(2)).sum::<f64>()).sqrt(),
+            leave_one_out,
+        }
+    }
+
+    /// Statistic of `data` minus its jackknife bias estimate (at least two observations).
+    pub fn jackknife_bias_corrected_estimate<S: Fn(&[T]) -> f64>(data: &[T], statistic: S) -> f64 {
+        let unused = DiscreteFiniteRandomExperiment::new(Vec::new(), &[]);
+        let bias = unused.jackknife_estimate(data, &statistic).bias_estimate;
+        statistic(data) - bias
+    }
+}
+
+/// Subsampling, `self` is not used.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Values of `statistic` over `n_subsamples` subsets of `subsample_size` observations
+    /// drawn without replacement from `data`.
+    pub fn subsample_distribution<R: Rng, S: Fn(&[T]) -> f64>(&self, rng: &mut R, data: Vec<T>, statistic: S, subsample_size: usize, n_subsamples: usize) -> Vec<f64> {
+        let weights = vec![1.0; data.len()];
+        let population = DiscreteFiniteRandomExperiment::new(data, &weights);
+        (0..n_subsamples)
+            .map(|_| statistic(&population.sample_without_replacement(rng, subsample_size)))
+            .collect()
+    }
+}
+
+/// Result of `cross_validate`.
+#[derive(Debug, Clone)]
+pub struct CrossValidationResult {
+    /// Mean over the folds of the held-out log-likelihood per observation.
+    pub mean_log_likelihood: f64,
+    /// Sample standard deviation of the fold results.
+    pub std_log_likelihood: f64,
+    /// Held-out log-likelihood per observation of each fold.
+    pub fold_results: Vec<f64>,
+}
+
+/// K-fold cross-validation of the maximum likelihood law over `0..n_outcomes`.
+///
+/// `data` is shuffled and split into `k_folds` folds of nearly equal sizes; the empirical law of the other
+/// folds is evaluated on each fold. Minus the mean log-likelihood estimates the cross entropy of the
+/// fitted law, above the entropy `H(P)`. An outcome of a fold absent from the other folds gives `-inf`.
+pub fn cross_validate<R: Rng>(data: Vec<usize>, n_outcomes: usize, k_folds: usize, rng: &mut R) -> CrossValidationResult {
+    assert!(k_folds >= 2 && k_folds <= data.len(), "need between 2 and {} folds", data.len());
+    let order = DiscreteFiniteRandomExperiment::uniform_permutation(rng, data.len());
+    let fold_of = |position: usize| position * k_folds / data.len();
+
+    let fold_results: Vec<f64> = (0..k_folds)
+        .map(|fold| {
+            let mut counts = vec![0.0; n_outcomes];
+            let mut held_out = Vec::new();
+            for (position, i) in order.iter().enumerate() {
+                if fold_of(position) == fold {
+                    held_out.push(data[*i]);
+                } else {
+                    counts[data[*i]] += 1.0;
+                }
+            }
+            let law = DiscreteFiniteDistribution::normalize(&counts);
+            held_out.iter().map(|x| law[*x].ln()).sum::<f64>() / held_out.len() as f64
+        })
+        .collect();
+
+    let (mean, variance) = mean_and_variance(&fold_results);
+    CrossValidationResult {
+        mean_log_likelihood: mean,
+        std_log_likelihood: variance.sqrt(),
+        fold_results,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn mean_difference(a: &[f64], b: &[f64]) -> f64 {
+        a.iter().sum::<f64>() / a.len() as f64 - b.iter().sum::<f64>() / b.len() as f64
+    }
+
+    #[test]
+    fn permutation_test_check() {
+        let mut rng = StdRng::seed_from_u64(140);
+        let exp = DiscreteFiniteRandomExperiment::new(vec![0.0], &[1.0]);
+
+        let low: Vec<f64> = (0..20).map(|i| i as f64).collect();
+        let high: Vec<f64> = (0..20).map(|i| 10.0 + i as f64).collect();
+        let res = exp.permutation_test(&mut rng, low.clone(), high, mean_difference, 2_000);
+        assert!((res.observed_statistic + 10.0).abs() < 1e-12);
+        assert!(res.p_value < 0.01);
+        assert!(res.null_mean.abs() < 0.5);
+
+        let mixed: Vec<f64> = (0..20).map(|i| i as f64 + 0.5).collect();
+        let res = exp.permutation_test(&mut rng, low, mixed, mean_difference, 2_000);
+        assert!(res.p_value > 0.5);
+    }
+
+    #[test]
+    fn bootstrap_check() {
+        let mut rng = StdRng::seed_from_u64(167);
+        let bernoulli = DiscreteFiniteRandomExperiment::new(vec![0u8, 1], &[0.7, 0.3]);
+        let mean = |xs: &[u8]| xs.iter().map(|x| *x as f64).sum::<f64>() / xs.len() as f64;
+
+        let trials = 100;
+        let covered = (0..trials)
+            .filter(|_| {
+                let data: Vec<u8> = (0..100).map(|_| bernoulli.sample(&mut rng)).collect();
+                let boot = bernoulli.bootstrap_distribution(&mut rng, data, mean, 200);
+                assert!(boot.omega.windows(2).all(|w| w[0] < w[1]));
+                let low = boot.omega[boot.distribution.quantile(0.025)];
+                let high = boot.omega[boot.distribution.quantile(0.975)];
+                low <= 0.3 && 0.3 <= high
+            })
+            .count();
+        let coverage = covered as f64 / trials as f64;
+        assert!((0.88..=1.0).contains(&coverage), "{coverage}");
+    }
+
+    #[test]
+    fn jackknife_check() {
+        let data = [2.0, 4.0, 4.0, 5.0, 7.0, 9.0, 11.0];
+        let n = data.len() as f64;
+        let biased_variance = |xs: &[f64]| {
+            let m = xs.iter().sum::<f64>() / xs.len() as f64;
+            xs.iter().map(|x| (x - m).powi(2)).sum::<f64>() / xs.len() as f64
+        };
+        let biased = biased_variance(&data);
+        let unbiased = biased * n / (n - 1.0);
+
+        let unused = DiscreteFiniteRandomExperiment::new(vec![0.0], &[1.0]);
+        let result = unused.jackknife_estimate(&data, biased_variance);
+        assert_eq!(result.leave_one_out.len(), data.len());
+        assert!((biased - result.bias_estimate - unbiased).abs() < 1e-9);
+        let corrected = DiscreteFiniteRandomExperiment::jackknife_bias_corrected_estimate(&data, biased_variance);
+        assert!((corrected - unbiased).abs() < 1e-9);
+
+        // standard error of the mean is s / sqrt(n)
+        let mean = |xs: &[f64]| xs.iter().sum::<f64>() / xs.len() as f64;
+        let result = unused.jackknife_estimate(&data, mean);
+        assert!(result.bias_estimate.abs() < 1e-9);
+        assert!((result.standard_error_estimate - (unbiased / n).sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "the jackknife needs at least two observations")]
+    fn jackknife_bias_corrected_single_observation() {
+        DiscreteFiniteRandomExperiment::jackknife_bias_corrected_estimate(&[1.0], |xs: &[f64]| xs[0]);
+    }
+
+    #[test]
+    fn subsampling_check() {
+        let mut rng = StdRng::seed_from_u64(169);
+        let die = DiscreteFiniteRandomExperiment::new(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[1.0; 6]);
+        let data: Vec<f64> = (0..2_000).map(|_| die.sample(&mut rng)).collect();
+        let mean = |xs: &[f64]| xs.iter().sum::<f64>() / xs.len() as f64;
+        let m = mean(&data);
+        let sigma = (data.iter().map(|x| (x - m).powi(2)).sum::<f64>() / data.len() as f64).sqrt();
+
+        let means = die.subsample_distribution(&mut rng, data, mean, 50, 500);
+        assert_eq!(means.len(), 500);
+        let (center, variance) = mean_and_variance(&means);
+        let standard_error = variance.sqrt();
+        assert!((center - m).abs() < 0.05);
+        assert!((standard_error / (sigma / 50f64.sqrt()) - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn cross_validation_check() {
+        let mut rng = StdRng::seed_from_u64(189);
+        let exp = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2, 3], &[0.2, 0.2, 0.3, 0.3]);
+        let entropy = exp.entropy();
+
+        let data: Vec<usize> = (0..1_000).map(|_| exp.sample(&mut rng)).collect();
+        let result = cross_validate(data, 4, 5, &mut rng);
+        assert_eq!(result.fold_results.len(), 5);
+        assert!((-result.mean_log_likelihood - entropy).abs() < 0.05);
+        assert!(result.std_log_likelihood > 0.0);
+
+        // the law fitted on 80 observations has a cross entropy about 3 / (2 * 80) above the entropy
+        let repetitions = 200;
+        let mean_cross_entropy = (0..repetitions)
+            .map(|_| {
+                let data: Vec<usize> = (0..100).map(|_| exp.sample(&mut rng)).collect();
+                -cross_validate(data, 4, 5, &mut rng).mean_log_likelihood
+            })
+            .sum::<f64>() / repetitions as f64;
+        assert!(mean_cross_entropy > entropy);
+        assert!(mean_cross_entropy - entropy < 0.04);
+    }
+}
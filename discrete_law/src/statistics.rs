@@ -0,0 +1,738 @@
+//! Standalone statistics of probability vectors, without building an experiment, and hypothesis tests.
+//!
+//! The `pmf_*` functions panic when the probability vector is not valid (see `pmf_is_valid`).
+
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::{standard_normal_cdf, total_variation_distance, DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment, SimulationResult};
+
+/// Tolerance on the total mass used to validate the inputs.
+const MASS_TOLERANCE: f64 = 1e-9;
+
+/// Whether `pmf` is a non empty vector of finite non negative numbers summing to 1 up to `eps`.
+pub fn pmf_is_valid(pmf: &[f64], eps: f64) -> bool {
+    !pmf.is_empty()
+        && pmf.iter().all(|p| p.is_finite() && *p >= 0.0)
+        && (pmf.iter().sum::<f64>() - 1.0).abs() <= eps
+}
+
+fn check_pmf(pmf: &[f64]) {
+    assert!(pmf_is_valid(pmf, MASS_TOLERANCE), "invalid probability vector {pmf:?}: probabilities must be non negative and sum to 1");
+}
+
+fn check_values(pmf: &[f64], values: &[f64]) {
+    check_pmf(pmf);
+    assert_eq!(pmf.len(), values.len(), "one value per probability is required");
+}
+
+/// Shannon entropy `-sum p_i ln p_i` in nats.
+pub fn pmf_entropy(pmf: &[f64]) -> f64 {
+    check_pmf(pmf);
+    -pmf.iter().filter(|p| **p > 0.0).map(|p| p * p.ln()).sum::<f64>()
+}
+
+/// Expected value `sum p_i * values_i`.
+pub fn pmf_expected_value(pmf: &[f64], values: &[f64]) -> f64 {
+    check_values(pmf, values);
+    pmf.iter().zip(values).map(|(p, x)| p * x).sum()
+}
+
+/// Variance `sum p_i * (values_i - mean)^2`.
+pub fn pmf_variance(pmf: &[f64], values: &[f64]) -> f64 {
+    let mean = pmf_expected_value(pmf, values);
+    pmf.iter().zip(values).map(|(p, x)| p * (x - mean).powi(2)).sum()
+}
+
+/// Total variation distance between two probability vectors of the same length.
+pub fn pmf_total_variation(p: &[f64], q: &[f64]) -> f64 {
+    check_pmf(p);
+    check_pmf(q);
+    total_variation_distance(p, q)
+}
+
+/// Two-sided p-value of a standard normal statistic.
+fn two_sided_normal_p_value(z: f64) -> f64 {
+    (2.0 * (1.0 - standard_normal_cdf(z.abs()))).min(1.0)
+}
+
+/// Result of `runs_test`.
+#[derive(Debug, Clone)]
+pub struct RunsTestResult {
+    /// Number of maximal blocks of equal consecutive values.
+    pub observed_runs: usize,
+    /// Expected number of runs given the counts of each value, under independence.
+    pub expected_runs: f64,
+    pub z_statistic: f64,
+    /// Two-sided p-value from the normal approximation.
+    pub p_value: f64,
+}
+
+/// Randomness tests.
+impl<T: PartialEq + Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Runs test on `n` draws: the number of runs is compared with its mean
+    /// `(N (N + 1) - sum n_i^2) / N` and variance given the counts `n_i` of each value.
+    pub fn runs_test<R: Rng>(&self, rng: &mut R, n: usize) -> RunsTestResult {
+        let samples: Vec<T> = (0..n).map(|_| self.sample(rng)).collect();
+        let observed_runs = if n == 0 { 0 } else { 1 + samples.windows(2).filter(|w| w[0] != w[1]).count() };
+
+        // count the draws of each distinct value
+        let mut counts = vec![0.0f64; self.omega.len()];
+        for x in &samples {
+            let first = self.omega.iter().position(|o| o == x).unwrap();
+            counts[first] += 1.0;
+        }
+        let big_n = n as f64;
+        let s2: f64 = counts.iter().map(|c| c * c).sum();
+        let s3: f64 = counts.iter().map(|c| c * c * c).sum();
+        let expected_runs = (big_n * (big_n + 1.0) - s2) / big_n;
+        let variance = (s2 * (s2 + big_n * (big_n + 1.0)) - 2.0 * big_n * s3 - big_n.powi(3))
+            / (big_n * big_n * (big_n - 1.0));
+
+        // a single distinct value gives a single run, nothing to test
+        let (z_statistic, p_value) = if variance > 0.0 {
+            let z = (observed_runs as f64 - expected_runs) / variance.sqrt();
+            (z, two_sided_normal_p_value(z))
+        } else {
+            (0.0, 1.0)
+        };
+        RunsTestResult { observed_runs, expected_runs, z_statistic, p_value }
+    }
+}
+
+/// Mean and sample variance (squared deviations from the mean divided by `n - 1`) of `values`, in two passes.
+pub(crate) fn mean_and_variance(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    (mean, values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0))
+}
+
+/// `ln sum_i exp(x_i)`, shifted by the largest `x_i` to avoid overflow.
+pub(crate) fn log_sum_exp(xs: &[f64]) -> f64 {
+    let shift = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    shift + xs.iter().map(|x| (x - shift).exp()).sum::<f64>().ln()
+}
+
+/// Law `exp(x_i) / sum_j exp(x_j)`, shifted by the largest `x_i` to avoid overflow.
+pub(crate) fn softmax(xs: &[f64]) -> Vec<f64> {
+    let shift = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    DiscreteFiniteDistribution::normalize(&xs.iter().map(|x| (x - shift).exp()).collect::<Vec<f64>>())
+}
+
+/// Logarithm of the gamma function for `x > 0` (Lanczos approximation).
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5 - (x + 0.5) * (x + 5.5).ln();
+    let series = COEFFICIENTS.iter()
+        .enumerate()
+        .fold(1.000000000190015, |acc, (j, c)| acc + c / (x + 1.0 + j as f64));
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Upper regularized incomplete gamma function `Q(a, x)`,
+/// by its series for `x < a + 1` and by a continued fraction otherwise.
+fn upper_regularized_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefactor = -x + a * x.ln() - ln_gamma(a);
+    if x < a + 1.0 {
+        let (mut term, mut sum, mut ap) = (1.0 / a, 1.0 / a, a);
+        for _ in 0..500 {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        1.0 - sum * log_prefactor.exp()
+    } else {
+        // modified Lentz algorithm
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny { d = tiny; }
+            c = b + an / c;
+            if c.abs() < tiny { c = tiny; }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        log_prefactor.exp() * h
+    }
+}
+
+/// Probability that a chi-square variable with `df` degrees of freedom exceeds `statistic`.
+pub fn chi_square_p_value(statistic: f64, df: usize) -> f64 {
+    upper_regularized_gamma(df as f64 / 2.0, statistic / 2.0).clamp(0.0, 1.0)
+}
+
+/// Result of `likelihood_ratio_test`.
+#[derive(Debug, Clone)]
+pub struct LikelihoodRatioResult {
+    /// `2 * sum count_i * ln(h1_i / h0_i)`.
+    pub statistic: f64,
+    pub degrees_of_freedom: usize,
+    /// Chi-square approximation of the p-value.
+    pub p_value: f64,
+}
+
+/// Likelihood ratio test of `h0_probs` against `h1_probs` from the counts of each category.
+///
+/// The degrees of freedom are those of a saturated alternative: the number of categories
+/// possible under H0 minus one. Categories with no count do not contribute to the statistic.
+pub fn likelihood_ratio_test(data_counts: &[usize], h0_probs: &[f64], h1_probs: &[f64]) -> LikelihoodRatioResult {
+    assert!(data_counts.len() == h0_probs.len() && data_counts.len() == h1_probs.len(), "one probability per category is required");
+    let statistic = 2.0 * data_counts.iter()
+        .zip(h0_probs.iter().zip(h1_probs))
+        .filter(|(c, _)| **c > 0)
+        .map(|(c, (p0, p1))| *c as f64 * (p1 / p0).ln())
+        .sum::<f64>();
+    let degrees_of_freedom = h0_probs.iter().filter(|p| **p > 0.0).count().saturating_sub(1);
+    LikelihoodRatioResult { statistic, degrees_of_freedom, p_value: chi_square_p_value(statistic, degrees_of_freedom) }
+}
+
+/// Value exceeded with probability `alpha` by a chi-square variable with `df` degrees of freedom, by bisection.
+fn chi_square_critical_value(alpha: f64, df: usize) -> f64 {
+    let (mut low, mut high) = (0.0, 1.0);
+    while chi_square_p_value(high, df) > alpha {
+        high *= 2.0;
+    }
+    for _ in 0..100 {
+        let middle = 0.5 * (low + high);
+        if chi_square_p_value(middle, df) > alpha {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    0.5 * (low + high)
+}
+
+/// Number of points of the grid swept by `profile_likelihood_ci`.
+const PROFILE_GRID_SIZE: usize = 10_001;
+
+/// Profile log-likelihood of `p[target_index]` on `n_points` equally spaced values of `[0, 1]`.
+///
+/// With `p[target_index] = p` fixed, the likelihood of the counts is maximized by sharing `1 - p`
+/// among the other categories in proportion to their counts, which gives
+/// `n_t ln p + (N - n_t) ln(1 - p)` up to a constant. The full log-likelihood `sum n_j ln p_j` is returned.
+pub fn profile_likelihood_curve(data_counts: &[usize], target_index: usize, n_points: usize) -> Vec<(f64, f64)> {
+    assert!(target_index < data_counts.len(), "target index out of the categories");
+    assert!(n_points >= 2, "at least two points are required");
+    let total: usize = data_counts.iter().sum();
+    let others = (total - data_counts[target_index]) as f64;
+    (0..n_points)
+        .map(|k| {
+            let p = k as f64 / (n_points - 1) as f64;
+            let log_likelihood = data_counts.iter()
+                .enumerate()
+                .filter(|(_, c)| **c > 0)
+                .map(|(j, c)| {
+                    let p_j = if j == target_index { p } else { (1.0 - p) * *c as f64 / others };
+                    *c as f64 * p_j.ln()
+                })
+                .sum();
+            (p, log_likelihood)
+        })
+        .collect()
+}
+
+/// Profile likelihood confidence interval of level `1 - alpha` for `p[target_index]`: the values of the grid
+/// whose profile log-likelihood is within half the chi-square (1 degree of freedom) critical value of its maximum.
+pub fn profile_likelihood_ci(data_counts: &[usize], target_index: usize, alpha: f64) -> (f64, f64) {
+    let curve = profile_likelihood_curve(data_counts, target_index, PROFILE_GRID_SIZE);
+    let maximum = curve.iter().map(|(_, l)| *l).fold(f64::NEG_INFINITY, f64::max);
+    let threshold = maximum - 0.5 * chi_square_critical_value(alpha, 1);
+    // the profile is unimodal, the values above the threshold form an interval
+    let inside: Vec<f64> = curve.iter().filter(|(_, l)| *l >= threshold).map(|(p, _)| *p).collect();
+    (inside[0], inside[inside.len() - 1])
+}
+
+/// Goodness of fit.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Likelihood ratio (G) test of the law `expected` against the empirical frequencies of `sim`.
+    pub fn goodness_of_fit_lr_test(sim: &SimulationResult<T>, expected: &[f64]) -> LikelihoodRatioResult {
+        likelihood_ratio_test(&sim.counts, expected, &sim.frequencies())
+    }
+}
+
+/// Result of `anderson_darling_test`.
+#[derive(Debug, Clone)]
+pub struct AdarlingResult {
+    pub statistic: f64,
+    /// Approximated from the asymptotic law of the continuous statistic, conservative for discrete laws.
+    pub p_value: f64,
+}
+
+/// Asymptotic cumulative distribution function of the Anderson-Darling statistic (Marsaglia and Marsaglia, 2004).
+fn anderson_darling_cdf(z: f64) -> f64 {
+    if z <= 0.0 {
+        0.0
+    } else if z < 2.0 {
+        (-1.2337141 / z).exp() / z.sqrt()
+            * (2.00012 + (0.247105 - (0.0649821 - (0.0347962 - (0.011672 - 0.00168691 * z) * z) * z) * z) * z)
+    } else {
+        (-(1.0776 - (2.30695 - (0.43424 - (0.082433 - (0.008056 - 0.0003146 * z) * z) * z) * z) * z).exp()).exp()
+    }
+}
+
+/// Goodness of fit.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Anderson-Darling test of the counts of `sim` against `expected_cdf[i] = P(X <= omega[i])`,
+    /// the outcomes of `sim` being in increasing order.
+    ///
+    /// The discrete form of the statistic (Choulakian, Lockhart and Stephens) is used:
+    /// `A^2 = n * sum_j (Z_j - H_j)^2 p_j / (H_j (1 - H_j))` with `Z` the empirical and `H` the expected
+    /// cumulative distribution, `p_j = H_j - H_(j-1)`, over the outcomes with `0 < H_j < 1`.
+    pub fn anderson_darling_test(sim: &SimulationResult<T>, expected_cdf: &[f64]) -> AdarlingResult {
+        assert_eq!(sim.counts.len(), expected_cdf.len(), "one cumulative probability per outcome is required");
+        let n = sim.n() as f64;
+        let mut empirical = 0.0;
+        let mut previous = 0.0;
+        let mut statistic = 0.0;
+        for (count, h) in sim.counts.iter().zip(expected_cdf) {
+            empirical += *count as f64 / n;
+            if *h > 0.0 && *h < 1.0 {
+                statistic += (empirical - h).powi(2) * (h - previous) / (h * (1.0 - h));
+            }
+            previous = *h;
+        }
+        statistic *= n;
+        AdarlingResult { statistic, p_value: (1.0 - anderson_darling_cdf(statistic)).clamp(0.0, 1.0) }
+    }
+}
+
+/// Result of `ks_test`.
+#[derive(Debug, Clone)]
+pub struct KsTestResult {
+    pub statistic: f64,
+    /// Asymptotic critical value `sqrt(-ln(alpha / 2) / 2) / sqrt(n)`, conservative for discrete laws.
+    pub critical_value: f64,
+    pub reject: bool,
+}
+
+/// Kolmogorov-Smirnov test.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// `max_i |F_n(omega_i) - theoretical_cdf[i]|`, the outcomes of `sim` being in increasing order.
+    /// Both cumulative distributions are step functions jumping at the outcomes, so the supremum is reached there.
+    pub fn ks_statistic(sim: &SimulationResult<T>, theoretical_cdf: &[f64]) -> f64 {
+        assert_eq!(sim.counts.len(), theoretical_cdf.len(), "one cumulative probability per outcome is required");
+        sim.frequencies()
+            .iter()
+            .scan(0.0, |empirical, f| {
+                *empirical += f;
+                Some(*empirical)
+            })
+            .zip(theoretical_cdf)
+            .map(|(e, f)| (e - f).abs())
+            .fold(0.0, f64::max)
+    }
+
+    /// Kolmogorov-Smirnov test at level `alpha` with the asymptotic critical value.
+    pub fn ks_test(sim: &SimulationResult<T>, theoretical_cdf: &[f64], alpha: f64) -> KsTestResult {
+        let statistic = Self::ks_statistic(sim, theoretical_cdf);
+        let critical_value = (-0.5 * (alpha / 2.0).ln()).sqrt() / (sim.n() as f64).sqrt();
+        KsTestResult { statistic, critical_value, reject: statistic > critical_value }
+    }
+}
+
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Kolmogorov-Smirnov statistics of `n_simulations` samples of size `n` of `self` against its own law,
+    /// the exact null distribution for this discrete law (smaller than the continuous one).
+    pub fn simulate_ks_null_distribution<R: Rng>(&self, rng: &mut R, n: usize, n_simulations: usize) -> Vec<f64> {
+        let cdf: Vec<f64> = self.distribution.probabilities()
+            .iter()
+            .scan(0.0, |acc, p| {
+                *acc += p;
+                Some(*acc)
+            })
+            .collect();
+        (0..n_simulations)
+            .map(|_| Self::ks_statistic(&self.simulate(rng, n), &cdf))
+            .collect()
+    }
+}
+
+/// Benjamini-Hochberg procedure: with the p-values sorted increasingly, reject the `k` smallest for the largest `k`
+/// such that `p_(k) <= k alpha / m`. The expected proportion of false discoveries among the rejections is at most
+/// `alpha` for independent tests. Return whether each test is rejected, in the order of `p_values`.
+pub fn benjamini_hochberg(p_values: &[f64], alpha: f64) -> Vec<bool> {
+    let m = p_values.len();
+    let mut order: Vec<usize> = (0..m).collect();
+    order.sort_by(|a, b| p_values[*a].total_cmp(&p_values[*b]));
+    let n_rejected = (1..=m)
+        .rev()
+        .find(|k| p_values[order[k - 1]] <= *k as f64 * alpha / m as f64)
+        .unwrap_or(0);
+    let mut rejected = vec![false; m];
+    for i in &order[..n_rejected] {
+        rejected[*i] = true;
+    }
+    rejected
+}
+
+/// Result of `simulate_fdr`.
+#[derive(Debug, Clone)]
+pub struct FdrResult {
+    /// Mean proportion of true null hypotheses among the rejected ones (0 without rejection).
+    pub empirical_fdr: f64,
+    /// Mean proportion of false null hypotheses that are rejected (0 without false null hypothesis).
+    pub empirical_power: f64,
+}
+
+/// Power analysis.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Proportion of `n_simulations` samples of size `n` of `alt_experiment` rejected by `test`,
+    /// a test of `null_experiment` at level `alpha` returning `true` when it rejects.
+    pub fn compute_power<R: Rng>(null_experiment: &Self, alt_experiment: &Self, rng: &mut R, n: usize, alpha: f64, test: impl Fn(&SimulationResult<T>) -> bool, n_simulations: usize) -> f64 {
+        assert_eq!(null_experiment.omega.len(), alt_experiment.omega.len(), "both hypotheses must have the same outcomes");
+        assert!(alpha > 0.0 && alpha < 1.0, "the level must be in (0, 1)");
+        let rejections = (0..n_simulations).filter(|_| test(&alt_experiment.simulate(rng, n))).count();
+        rejections as f64 / n_simulations as f64
+    }
+
+    /// Smallest sample size of `n_range` at which the likelihood ratio goodness of fit test of the law of
+    /// `null_experiment` at level `alpha` rejects samples of `alt_experiment` with a simulated power of at least
+    /// `target_power`, by binary search (the power growing with the sample size). `None` when the largest
+    /// sample size of the range isn't enough.
+    pub fn determine_sample_size<R: Rng>(null_experiment: &Self, alt_experiment: &Self, rng: &mut R, alpha: f64, target_power: f64, n_range: RangeInclusive<usize>, n_simulations: usize) -> Option<usize> {
+        let null_law = null_experiment.distribution.probabilities();
+        let test = |sim: &SimulationResult<T>| Self::goodness_of_fit_lr_test(sim, &null_law).p_value < alpha;
+        let mut power = |n: usize| Self::compute_power(null_experiment, alt_experiment, rng, n, alpha, test, n_simulations);
+
+        let (mut low, mut high) = n_range.into_inner();
+        if low > high || power(high) < target_power {
+            return None;
+        }
+        // the power reaches the target at `high` and not below `low`
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if power(middle) >= target_power {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        Some(high)
+    }
+
+    /// Law over the 10 bins `[0, 0.1), ..., [0.9, 1]` of the p-values `test(sample)` of `n_simulations` samples
+    /// of size `n` of `null_experiment`, uniform when the p-values are valid.
+    pub fn simulate_null_p_value_distribution<R: Rng>(null_experiment: &Self, rng: &mut R, n: usize, test: impl Fn(&SimulationResult<T>) -> f64, n_simulations: usize) -> DiscreteFiniteRandomExperiment<usize> {
+        assert!(n_simulations > 0, "at least one simulation is required");
+        let mut counts = [0; 10];
+        for _ in 0..n_simulations {
+            let p_value = test(&null_experiment.simulate(rng, n));
+            assert!((0.0..=1.0).contains(&p_value), "p-values must be in [0, 1], got {p_value}");
+            counts[((p_value * 10.0) as usize).min(9)] += 1;
+        }
+        DiscreteFiniteRandomExperiment::from_counts(counts.into_iter().enumerate())
+    }
+
+    /// Multiple testing with the Benjamini-Hochberg procedure at level `alpha`: in each of the `n_simulations`
+    /// simulations, test `j` is the likelihood ratio goodness of fit test of the law of `null_experiments[j]`
+    /// on `n` draws of `alt_experiments[j]`. The null hypothesis `j` is true when both laws are equal.
+    pub fn simulate_fdr<R: Rng>(null_experiments: &[Self], alt_experiments: &[Self], n: usize, alpha: f64, rng: &mut R, n_simulations: usize) -> FdrResult {
+        assert_eq!(null_experiments.len(), alt_experiments.len(), "one alternative per null hypothesis is required");
+        let null_laws: Vec<Vec<f64>> = null_experiments.iter().map(|e| e.distribution.probabilities()).collect();
+        let true_null: Vec<bool> = null_laws.iter()
+            .zip(alt_experiments)
+            .map(|(law, alt)| *law == alt.distribution.probabilities())
+            .collect();
+        let n_false_nulls = true_null.iter().filter(|t| !**t).count();
+
+        let (mut fdr, mut power) = (0.0, 0.0);
+        for _ in 0..n_simulations {
+            let p_values: Vec<f64> = alt_experiments.iter()
+                .zip(&null_laws)
+                .map(|(alt, law)| Self::goodness_of_fit_lr_test(&alt.simulate(rng, n), law).p_value)
+                .collect();
+            let rejected = benjamini_hochberg(&p_values, alpha);
+            let n_rejected = rejected.iter().filter(|r| **r).count();
+            let false_discoveries = rejected.iter().zip(&true_null).filter(|(r, t)| **r && **t).count();
+            if n_rejected > 0 {
+                fdr += false_discoveries as f64 / n_rejected as f64;
+            }
+            if n_false_nulls > 0 {
+                power += (n_rejected - false_discoveries) as f64 / n_false_nulls as f64;
+            }
+        }
+        FdrResult { empirical_fdr: fdr / n_simulations as f64, empirical_power: power / n_simulations as f64 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn pmf_is_valid_check() {
+        assert!(pmf_is_valid(&[0.2, 0.8], 1e-12));
+        assert!(pmf_is_valid(&[0.5, 0.5 + 1e-10], 1e-9));
+        assert!(!pmf_is_valid(&[], 1e-9));
+        assert!(!pmf_is_valid(&[0.5, 0.6], 1e-9));
+        assert!(!pmf_is_valid(&[1.5, -0.5], 1e-9));
+        assert!(!pmf_is_valid(&[f64::NAN, 1.0], 1e-9));
+    }
+
+    #[test]
+    fn pmf_entropy_check() {
+        assert!((pmf_entropy(&[0.25; 4]) - 4f64.ln()).abs() < 1e-12);
+        assert_eq!(pmf_entropy(&[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn pmf_moments_check() {
+        let die = [1.0 / 6.0; 6];
+        let faces = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        assert!((pmf_expected_value(&die, &faces) - 3.5).abs() < 1e-12);
+        assert!((pmf_variance(&die, &faces) - 35.0 / 12.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pmf_total_variation_check() {
+        assert!((pmf_total_variation(&[0.3, 0.7], &[0.7, 0.3]) - 0.4).abs() < 1e-12);
+        assert_eq!(pmf_total_variation(&[1.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid probability vector")]
+    fn pmf_invalid_input_check() {
+        pmf_expected_value(&[0.5, 0.6], &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn runs_test_check() {
+        let mut rng = StdRng::seed_from_u64(153);
+        let coin = DiscreteFiniteRandomExperiment::new(vec![false, true], &[0.5, 0.5]);
+
+        let result = coin.runs_test(&mut rng, 1_000);
+        assert!((result.expected_runs - 501.0).abs() < 5.0);
+
+        let repetitions = 400;
+        let rejections = (0..repetitions).filter(|_| coin.runs_test(&mut rng, 200).p_value < 0.05).count();
+        assert!((rejections as f64 / repetitions as f64) < 0.08);
+
+        let constant = DiscreteFiniteRandomExperiment::new(vec!['a', 'b'], &[1.0, 0.0]);
+        let result = constant.runs_test(&mut rng, 50);
+        assert_eq!(result.observed_runs, 1);
+        assert_eq!(result.p_value, 1.0);
+    }
+
+    #[test]
+    fn likelihood_ratio_check() {
+        let mut rng = StdRng::seed_from_u64(154);
+        assert!((chi_square_p_value(3.841459, 1) - 0.05).abs() < 1e-6);
+        assert!((chi_square_p_value(2.0, 2) - (-1f64).exp()).abs() < 1e-9);
+        assert!((chi_square_p_value(30.0, 20) - 0.0699).abs() < 1e-4);
+
+        let die = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0, 1.0, 1.0, 1.0, 1.0, 1.2]);
+        let fair = [1.0 / 6.0; 6];
+        let sim = die.simulate(&mut rng, 20_000);
+        assert_eq!(sim.n(), 20_000);
+
+        let lr = DiscreteFiniteRandomExperiment::goodness_of_fit_lr_test(&sim, &fair);
+        assert_eq!(lr.degrees_of_freedom, 5);
+        let pearson: f64 = sim.counts.iter()
+            .zip(fair)
+            .map(|(c, p)| (*c as f64 - 20_000.0 * p).powi(2) / (20_000.0 * p))
+            .sum();
+        assert!((lr.statistic / pearson - 1.0).abs() < 0.05);
+        assert!((lr.p_value - chi_square_p_value(pearson, 5)).abs() < 0.01);
+        assert!(lr.p_value < 1e-3);
+    }
+
+    #[test]
+    fn anderson_darling_check() {
+        let mut rng = StdRng::seed_from_u64(159);
+        // 5% critical value of the asymptotic law
+        assert!((anderson_darling_cdf(2.492) - 0.95).abs() < 1e-3);
+
+        let law = [0.1, 0.2, 0.3, 0.25, 0.15];
+        let cdf: Vec<f64> = law.iter().scan(0.0, |acc, p| { *acc += p; Some(*acc) }).collect();
+        let exp = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5], &law);
+
+        let repetitions = 500;
+        let rejections = (0..repetitions)
+            .filter(|_| DiscreteFiniteRandomExperiment::anderson_darling_test(&exp.simulate(&mut rng, 200), &cdf).p_value < 0.05)
+            .count();
+        assert!((rejections as f64 / repetitions as f64) < 0.06);
+
+        let shifted = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5], &[0.3, 0.3, 0.2, 0.1, 0.1]);
+        let result = DiscreteFiniteRandomExperiment::anderson_darling_test(&shifted.simulate(&mut rng, 200), &cdf);
+        assert!(result.p_value < 0.01);
+    }
+
+    #[test]
+    fn ks_check() {
+        let mut rng = StdRng::seed_from_u64(170);
+        let law = [0.05, 0.1, 0.15, 0.2, 0.2, 0.15, 0.1, 0.05];
+        let cdf: Vec<f64> = law.iter().scan(0.0, |acc, p| { *acc += p; Some(*acc) }).collect();
+        let exp = DiscreteFiniteRandomExperiment::new((1..=8).collect(), &law);
+        let sim = SimulationResult { omega: vec![1, 2], counts: vec![3, 1] };
+        assert!((DiscreteFiniteRandomExperiment::ks_statistic(&sim, &[0.5, 1.0]) - 0.25).abs() < 1e-12);
+
+        let alpha = 0.05;
+        let mut null = exp.simulate_ks_null_distribution(&mut rng, 100, 2_000);
+        null.sort_by(f64::total_cmp);
+        let critical = null[(0.95 * null.len() as f64) as usize];
+
+        let (mut simulated_rejections, mut asymptotic_rejections) = (0, 0);
+        for _ in 0..2_000 {
+            let sim = exp.simulate(&mut rng, 100);
+            let test = DiscreteFiniteRandomExperiment::ks_test(&sim, &cdf, alpha);
+            simulated_rejections += (test.statistic > critical) as usize;
+            asymptotic_rejections += test.reject as usize;
+        }
+        assert!((simulated_rejections as f64 / 2_000.0 - alpha).abs() < 0.015);
+        // the continuous critical value is conservative for a discrete law
+        assert!((asymptotic_rejections as f64 / 2_000.0) < alpha);
+    }
+
+    #[test]
+    fn profile_likelihood_check() {
+        let mut rng = StdRng::seed_from_u64(176);
+        assert!((chi_square_critical_value(0.05, 1) - 3.841459).abs() < 1e-5);
+
+        let curve = profile_likelihood_curve(&[30, 50, 20], 0, 11);
+        assert_eq!(curve.len(), 11);
+        assert_eq!(curve[0].1, f64::NEG_INFINITY);
+        let best = curve.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        assert!((best.0 - 0.3).abs() < 1e-12);
+
+        let (low, high) = profile_likelihood_ci(&[30, 50, 20], 0, 0.05);
+        assert!(low < 0.3 && 0.3 < high);
+        // close to the Wald interval 0.3 -/+ 1.96 sqrt(0.3 * 0.7 / 100) for this sample size
+        assert!((low - 0.21).abs() < 0.02 && (high - 0.39).abs() < 0.02);
+
+        let exp = DiscreteFiniteRandomExperiment::new(vec!['a', 'b', 'c'], &[0.3, 0.5, 0.2]);
+        let repetitions = 1_000;
+        let covered = (0..repetitions)
+            .filter(|_| {
+                let (low, high) = profile_likelihood_ci(&exp.simulate(&mut rng, 100).counts, 0, 0.05);
+                low <= 0.3 && 0.3 <= high
+            })
+            .count();
+        assert!((covered as f64 / repetitions as f64 - 0.95).abs() < 0.025);
+    }
+
+    #[test]
+    fn power_check() {
+        let mut rng = StdRng::seed_from_u64(195);
+        let null = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.7, 0.3]);
+        let alternative = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5]);
+        let alpha = 0.05;
+        let pearson = |sim: &SimulationResult<i32>| {
+            let n = sim.n() as f64;
+            let statistic: f64 = sim.counts.iter().zip([0.7, 0.3]).map(|(c, p)| (*c as f64 - n * p).powi(2) / (n * p)).sum();
+            chi_square_p_value(statistic, 1) < alpha
+        };
+
+        let powers: Vec<f64> = [10, 30, 100, 1_000].iter()
+            .map(|n| DiscreteFiniteRandomExperiment::compute_power(&null, &alternative, &mut rng, *n, alpha, pearson, 2_000))
+            .collect();
+        assert!(powers.windows(2).all(|w| w[0] < w[1]), "{powers:?}");
+        assert_eq!(powers[3], 1.0);
+
+        // under the null the rejection rate is the level
+        let size = DiscreteFiniteRandomExperiment::compute_power(&null, &null, &mut rng, 1_000, alpha, pearson, 2_000);
+        assert!((size - alpha).abs() < 0.015);
+    }
+
+    #[test]
+    fn sample_size_check() {
+        let mut rng = StdRng::seed_from_u64(196);
+        let bernoulli = |p: f64| DiscreteFiniteRandomExperiment::new(vec![0, 1], &[1.0 - p, p]);
+
+        let n = DiscreteFiniteRandomExperiment::determine_sample_size(&bernoulli(0.1), &bernoulli(0.9), &mut rng, 0.05, 0.8, 1..=2_000, 500);
+        assert!(n.unwrap() < 20);
+
+        // normal approximation: ((1.96 * 0.5 + 0.84 * 0.497) / 0.05)^2, about 780
+        let n = DiscreteFiniteRandomExperiment::determine_sample_size(&bernoulli(0.5), &bernoulli(0.55), &mut rng, 0.05, 0.8, 1..=2_000, 500).unwrap();
+        assert!((600..=1_000).contains(&n), "{n}");
+
+        let not_enough = DiscreteFiniteRandomExperiment::determine_sample_size(&bernoulli(0.5), &bernoulli(0.55), &mut rng, 0.05, 0.8, 1..=100, 500);
+        assert_eq!(not_enough, None);
+    }
+
+    #[test]
+    fn null_p_values_check() {
+        let mut rng = StdRng::seed_from_u64(199);
+        let die = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let pearson = |sim: &SimulationResult<i32>| {
+            let expected = sim.n() as f64 / 6.0;
+            let statistic: f64 = sim.counts.iter().map(|c| (*c as f64 - expected).powi(2) / expected).sum();
+            chi_square_p_value(statistic, 5)
+        };
+        let n_simulations = 2_000;
+        let bins = DiscreteFiniteRandomExperiment::simulate_null_p_value_distribution(&die, &mut rng, 500, pearson, n_simulations);
+        assert_eq!(bins.omega, (0..10).collect::<Vec<usize>>());
+
+        let counts: Vec<usize> = bins.distribution.probabilities().iter().map(|p| (p * n_simulations as f64).round() as usize).collect();
+        assert_eq!(counts.iter().sum::<usize>(), n_simulations);
+        let uniformity = likelihood_ratio_test(&counts, &[0.1; 10], &bins.distribution.probabilities());
+        assert!(uniformity.p_value > 0.01, "{uniformity:?}");
+
+        // p-values of a test of the wrong law pile up in the first bin
+        let loaded = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0, 1.0, 1.0, 1.0, 1.0, 1.5]);
+        let bins = DiscreteFiniteRandomExperiment::simulate_null_p_value_distribution(&loaded, &mut rng, 500, pearson, 500);
+        assert!(bins.distribution.probabilities()[0] > 0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "p-values must be in [0, 1], got NaN")]
+    fn null_p_values_nan() {
+        let mut rng = StdRng::seed_from_u64(199);
+        let coin = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5]);
+        DiscreteFiniteRandomExperiment::simulate_null_p_value_distribution(&coin, &mut rng, 10, |_| f64::NAN, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one simulation is required")]
+    fn null_p_values_no_simulation() {
+        let mut rng = StdRng::seed_from_u64(199);
+        let coin = DiscreteFiniteRandomExperiment::new(vec![0, 1], &[0.5, 0.5]);
+        DiscreteFiniteRandomExperiment::simulate_null_p_value_distribution(&coin, &mut rng, 10, |_| 0.5, 0);
+    }
+
+    #[test]
+    fn benjamini_hochberg_check() {
+        let mut rng = StdRng::seed_from_u64(200);
+        // thresholds k * 0.05 / 5: 0.01, 0.02, 0.03, 0.04, 0.05
+        assert_eq!(benjamini_hochberg(&[0.04, 0.001, 0.5, 0.025, 0.03], 0.05), vec![true, true, false, true, true]);
+        assert_eq!(benjamini_hochberg(&[0.02, 0.03], 0.01), vec![false, false]);
+        assert!(benjamini_hochberg(&[], 0.05).is_empty());
+
+        let die = || DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let loaded = || DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0, 1.0, 1.0, 1.0, 1.0, 2.0]);
+        let nulls: Vec<_> = (0..10).map(|_| die()).collect();
+
+        let all_null: Vec<_> = (0..10).map(|_| die()).collect();
+        let result = DiscreteFiniteRandomExperiment::simulate_fdr(&nulls, &all_null, 200, 0.1, &mut rng, 500);
+        assert!(result.empirical_fdr < 0.1 + 0.03, "{result:?}");
+        assert_eq!(result.empirical_power, 0.0);
+
+        let all_alternative: Vec<_> = (0..10).map(|_| loaded()).collect();
+        let result = DiscreteFiniteRandomExperiment::simulate_fdr(&nulls, &all_alternative, 200, 0.1, &mut rng, 500);
+        assert_eq!(result.empirical_fdr, 0.0);
+        assert!(result.empirical_power > 0.8, "{result:?}");
+    }
+}
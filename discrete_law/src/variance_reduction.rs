@@ -0,0 +1,213 @@
+//! Variance reduction techniques for Monte Carlo estimates.
+
+use rand::Rng;
+use rand::distr::Distribution;
+
+use crate::statistics::mean_and_variance;
+use crate::{DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment};
+
+/// Result of `stratified_sample`.
+#[derive(Debug, Clone)]
+pub struct StratifiedResult<T> {
+    /// Probability of each stratum.
+    pub strata_weights: Vec<f64>,
+    /// Draws from the conditional law of each stratum.
+    pub samples: Vec<Vec<T>>,
+    /// Frequency of each outcome within each stratum.
+    pub frequencies: Vec<Vec<f64>>,
+    /// Estimated law, the within-stratum frequencies weighted by the strata probabilities.
+    pub estimate: Vec<f64>,
+}
+
+/// Stratified sampling.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Draw `n_per_stratum` outcomes from the law conditioned on each stratum `0..n_strata`,
+    /// the stratum of an outcome being given by `strata_fn`. Strata of probability 0 are not sampled.
+    pub fn stratified_sample<R: Rng, F: Fn(&T) -> usize>(&self, rng: &mut R, strata_fn: F, n_strata: usize, n_per_stratum: usize) -> StratifiedResult<T> {
+        let law = self.distribution.probabilities();
+        let strata: Vec<usize> = self.omega.iter().map(&strata_fn).collect();
+        let mut strata_weights = vec![0.0; n_strata];
+        for (h, p) in strata.iter().zip(&law) {
+            strata_weights[*h] += p;
+        }
+
+        let mut samples = Vec::with_capacity(n_strata);
+        let mut frequencies = Vec::with_capacity(n_strata);
+        let mut estimate = vec![0.0; law.len()];
+        for (h, weight) in strata_weights.iter().enumerate() {
+            if *weight == 0.0 {
+                samples.push(Vec::new());
+                frequencies.push(vec![0.0; law.len()]);
+                continue;
+            }
+            let conditional: Vec<f64> = law.iter().zip(&strata).map(|(p, s)| if *s == h { *p } else { 0.0 }).collect();
+            let conditional = DiscreteFiniteDistribution::new(&conditional);
+            let draws: Vec<usize> = (0..n_per_stratum).map(|_| conditional.sample(rng)).collect();
+            let mut frequency = vec![0.0; law.len()];
+            for i in &draws {
+                frequency[*i] += 1.0 / n_per_stratum as f64;
+            }
+            samples.push(draws.iter().map(|i| self.omega[*i].clone()).collect());
+            for (e, f) in estimate.iter_mut().zip(&frequency) {
+                *e += weight * f;
+            }
+            frequencies.push(frequency);
+        }
+        StratifiedResult { strata_weights, samples, frequencies, estimate }
+    }
+}
+
+
+/// Control variates.
+impl<T: Clone> DiscreteFiniteRandomExperiment<T> {
+    /// Estimate `E[estimator(X)]` from `n` draws corrected by `control`, whose mean `control_mean` is known.
+    /// Return the estimate `mean(f) + c * (mean(g) - control_mean)` and the coefficient
+    /// `c = -Cov(f, g) / Var(g)` estimated from the same draws.
+    pub fn control_variate_estimate<R: Rng, F: Fn(&T) -> f64, G: Fn(&T) -> f64>(&self, rng: &mut R, estimator: F, control: G, control_mean: f64, n: usize) -> (f64, f64) {
+        let draws: Vec<(f64, f64)> = (0..n)
+            .map(|_| {
+                let x = self.sample(rng);
+                (estimator(&x), control(&x))
+            })
+            .collect();
+        let nf = n as f64;
+        let mean_f = draws.iter().map(|(f, _)| f).sum::<f64>() / nf;
+        let mean_g = draws.iter().map(|(_, g)| g).sum::<f64>() / nf;
+        let covariance = draws.iter().map(|(f, g)| (f - mean_f) * (g - mean_g)).sum::<f64>();
+        let variance = draws.iter().map(|(_, g)| (g - mean_g).powi(2)).sum::<f64>();
+        let c = if variance > 0.0 { -covariance / variance } else { 0.0 };
+        (mean_f + c * (mean_g - control_mean), c)
+    }
+}
+
+/// Antithetic variates.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Estimate `E[f(X)]` from `n_pairs` pairs of draws `(q(u), q(1 - u))` with `q` the quantile function.
+    /// Return the estimate and its variance, the sample variance of the pair averages divided by `n_pairs`.
+    pub fn antithetic_sample<R: Rng, F: Fn(&T) -> f64>(&self, rng: &mut R, f: F, n_pairs: usize) -> (f64, f64) {
+        let averages: Vec<f64> = (0..n_pairs)
+            .map(|_| {
+                let u: f64 = rng.random();
+                let x = &self.omega[self.distribution.quantile(u)];
+                let y = &self.omega[self.distribution.quantile(1.0 - u)];
+                0.5 * (f(x) + f(y))
+            })
+            .collect();
+        let (mean, variance) = mean_and_variance(&averages);
+        (mean, variance / n_pairs as f64)
+    }
+}
+
+/// First `n` terms of the van der Corput sequence in `base`: the radical inverses of `1, 2, ..., n`.
+pub fn van_der_corput(n: usize, base: usize) -> Vec<f64> {
+    assert!(base >= 2, "base must be at least 2");
+    (1..=n)
+        .map(|mut k| {
+            let mut x = 0.0;
+            let mut scale = 1.0 / base as f64;
+            while k > 0 {
+                x += (k % base) as f64 * scale;
+                k /= base;
+                scale /= base as f64;
+            }
+            x
+        })
+        .collect()
+}
+
+/// Quasi-Monte Carlo.
+impl<T> DiscreteFiniteRandomExperiment<T> {
+    /// Estimate `E[f(X)]` from the first `n` points of `sequence` in `[0, 1]` mapped by the quantile function.
+    pub fn qmc_sample<F: Fn(&T) -> f64>(&self, sequence: impl Iterator<Item = f64>, f: F, n: usize) -> f64 {
+        let (sum, count) = sequence.take(n)
+            .map(|u| f(&self.omega[self.distribution.quantile(u)]))
+            .fold((0.0, 0usize), |(s, c), y| (s + y, c + 1));
+        sum / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn variance(xs: &[f64]) -> f64 {
+        mean_and_variance(xs).1
+    }
+
+    #[test]
+    fn stratified_check() {
+        let mut rng = StdRng::seed_from_u64(147);
+        let exp = DiscreteFiniteRandomExperiment::new(vec![0, 1, 2, 3], &[1.0, 1.0, 1.0, 1.0]);
+
+        let result = exp.stratified_sample(&mut rng, |x| x / 2, 2, 50);
+        assert_eq!(result.strata_weights, vec![0.5, 0.5]);
+        assert!(result.samples[1].iter().all(|x| *x >= 2));
+        assert_eq!(result.frequencies[0][2], 0.0);
+        assert!((result.estimate.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+
+        let (stratified, simple): (Vec<f64>, Vec<f64>) = (0..500)
+            .map(|_| {
+                let s = exp.stratified_sample(&mut rng, |x| x / 2, 2, 50).estimate[0];
+                (s, exp.distribution.empirical_law(&mut rng, 100)[0])
+            })
+            .unzip();
+        assert!(variance(&stratified) < 0.85 * variance(&simple));
+    }
+
+    #[test]
+    fn control_variate_check() {
+        let mut rng = StdRng::seed_from_u64(148);
+        let die = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let square = |x: &i32| (x * x) as f64;
+        let identity = |x: &i32| *x as f64;
+
+        let (estimate, c) = die.control_variate_estimate(&mut rng, square, identity, 3.5, 100_000);
+        assert!((estimate - 91.0 / 6.0).abs() < 0.02);
+        // Cov(X^2, X) / Var(X) = 7
+        assert!((c + 7.0).abs() < 0.05);
+
+        let (controlled, naive): (Vec<f64>, Vec<f64>) = (0..300)
+            .map(|_| {
+                let naive = (0..100).map(|_| square(&die.sample(&mut rng))).sum::<f64>() / 100.0;
+                (die.control_variate_estimate(&mut rng, square, identity, 3.5, 100).0, naive)
+            })
+            .unzip();
+        assert!(variance(&controlled) < 0.2 * variance(&naive));
+    }
+
+    #[test]
+    fn antithetic_check() {
+        let mut rng = StdRng::seed_from_u64(149);
+        let die = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        assert_eq!(die.distribution.quantile(0.0), 0);
+        assert_eq!(die.distribution.quantile(0.5), 2);
+        assert_eq!(die.distribution.quantile(1.0), 5);
+
+        let n_pairs = 10_000;
+        let (estimate, antithetic_variance) = die.antithetic_sample(&mut rng, |x| *x as f64, n_pairs);
+        assert!((estimate - 3.5).abs() < 0.02);
+        // crude estimator from the same 2 * n_pairs draws
+        let crude_variance = 35.0 / 12.0 / (2 * n_pairs) as f64;
+        assert!(antithetic_variance < 0.2 * crude_variance);
+    }
+
+    #[test]
+    fn qmc_check() {
+        let mut rng = StdRng::seed_from_u64(150);
+        assert_eq!(van_der_corput(4, 2), vec![0.5, 0.25, 0.75, 0.125]);
+        assert!((van_der_corput(3, 3)[2] - 1.0 / 9.0).abs() < 1e-15);
+
+        let die = DiscreteFiniteRandomExperiment::new(vec![1, 2, 3, 4, 5, 6], &[1.0; 6]);
+        let n = 1000;
+        let qmc = die.qmc_sample(van_der_corput(n, 2).into_iter(), |x| *x as f64, n);
+        assert!((qmc - 3.5).abs() < 0.01);
+
+        // mean absolute error of crude Monte Carlo with the same n is about 0.04
+        let crude_error = (0..100)
+            .map(|_| ((0..n).map(|_| die.sample(&mut rng) as f64).sum::<f64>() / n as f64 - 3.5).abs())
+            .sum::<f64>() / 100.0;
+        assert!((qmc - 3.5).abs() < crude_error);
+    }
+}
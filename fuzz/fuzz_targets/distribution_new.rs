@@ -0,0 +1,58 @@
+//! Constructors of laws fed with arbitrary weights (any bit pattern, NaN and infinities included).
+//!
+//! The checked constructors `try_new` either fail or give a valid cumulative distribution,
+//! the unchecked constructors `new` never panic.
+
+#![no_main]
+
+use discrete_law::{fuzz_cdf_from, DiscreteFiniteDistribution, DiscreteFiniteRandomExperiment};
+use libfuzzer_sys::fuzz_target;
+
+/// Non empty, non decreasing, in `[0, 1]` and ending at 1.
+fn assert_valid_cdf(cdf: &[f64]) {
+    assert!(!cdf.is_empty());
+    assert!(cdf.iter().all(|x| (0.0..=1.0).contains(x)), "{cdf:?}");
+    assert!(cdf.windows(2).all(|w| w[0] <= w[1]), "{cdf:?}");
+    assert_eq!(cdf[cdf.len() - 1], 1.0, "{cdf:?}");
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((omega_size, bytes)) = data.split_first() else {
+        return;
+    };
+    let law: Vec<f64> = bytes.chunks_exact(8)
+        .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    let valid = law.iter().all(|w| w.is_finite() && *w >= 0.0) && law.iter().any(|w| *w > 0.0);
+
+    let cdf = fuzz_cdf_from(&law);
+    assert_eq!(cdf.len(), law.len());
+    if valid {
+        assert_valid_cdf(&cdf);
+    }
+
+    let unchecked = DiscreteFiniteDistribution::new(&law);
+    assert_eq!(unchecked.len(), law.len());
+    match DiscreteFiniteDistribution::try_new(&law) {
+        Ok(d) => {
+            assert!(valid);
+            assert_valid_cdf(&d.cdf());
+            assert_eq!(d.cdf(), unchecked.cdf());
+            assert!(d.quantile(0.0) < d.len() && d.quantile(1.0) < d.len());
+        }
+        Err(_) => assert!(!valid),
+    }
+
+    // omega sizes around the number of weights, matching or not
+    let omega: Vec<usize> = (0..(*omega_size as usize % 8 + law.len()).saturating_sub(4)).collect();
+    let accepted = valid && omega.len() == law.len();
+    let unchecked = DiscreteFiniteRandomExperiment::new(omega.clone(), &law);
+    assert_eq!(unchecked.omega.len(), omega.len());
+    match DiscreteFiniteRandomExperiment::try_new(omega, &law) {
+        Ok(exp) => {
+            assert!(accepted && exp.omega.len() == law.len());
+            assert_valid_cdf(&exp.distribution.cdf());
+        }
+        Err(_) => assert!(!accepted),
+    }
+});